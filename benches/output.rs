@@ -0,0 +1,46 @@
+//! Compares buffered stdout output with writing every fragment straight to
+//! stdout, on the bundled examples. The timings go to stderr, so send the
+//! scripts' output to a pipe: `cargo bench --bench output | cat > /dev/null`.
+//! The output-heavy triangle example gains the most.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use minimal::{CallbackOutput, Interpreter, StdoutOutput};
+
+const EXAMPLES: [(&str, &str); 3] = [
+    ("prime_numbers", include_str!("../example/prime_numbers.txt")),
+    ("triangle", include_str!("../example/triangle.txt")),
+    ("pathagorean_triples", include_str!("../example/pathagorean_triples.txt"))
+];
+const RUNS: u32 = 20;
+
+fn time(source: &str, buffered: bool) -> Duration {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        let mut interpreter = Interpreter::new();
+        if buffered {
+            interpreter.set_output(StdoutOutput::new());
+        }
+        else {
+            interpreter.set_output(CallbackOutput(|s: &str| {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(s.as_bytes())?;
+                stdout.flush()
+            }));
+        }
+        interpreter.run_source(source).unwrap();
+    }
+    start.elapsed() / RUNS
+}
+
+fn main() {
+    for (name, source) in EXAMPLES {
+        let unbuffered = time(source, false);
+        let buffered = time(source, true);
+        eprintln!(
+            "{:<20} unbuffered {:>10.3?}  buffered {:>10.3?}  speedup {:.2}x",
+            name, unbuffered, buffered, unbuffered.as_secs_f64() / buffered.as_secs_f64()
+        );
+    }
+}
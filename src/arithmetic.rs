@@ -0,0 +1,210 @@
+/// How integer operations behave when the result does not fit in an `i64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Arithmetic {
+    #[default]
+    Wrapping,
+    Checked,
+    Saturating
+}
+
+/// How `/`, `%` and `divmod` round when the division is not exact.
+///
+/// | a, b   | Truncate | Floor  | Euclid |
+/// |--------|----------|--------|--------|
+/// |  7,  3 |  2,  1   |  2,  1 |  2, 1  |
+/// | -7,  3 | -2, -1   | -3,  2 | -3, 2  |
+/// |  7, -3 | -2,  1   | -3, -2 | -2, 1  |
+/// | -7, -3 |  2, -1   |  2, -1 |  3, 2  |
+///
+/// Truncate rounds the quotient toward zero and the remainder takes the
+/// dividend's sign. Floor rounds toward negative infinity and the remainder
+/// takes the divisor's sign. Euclid always gives a remainder in `0..|b|`.
+/// In every mode `a == b * (a / b) + a % b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Division {
+    #[default]
+    Truncate,
+    Floor,
+    Euclid
+}
+
+impl Division {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "truncate" => Some(Self::Truncate),
+            "floor" => Some(Self::Floor),
+            "euclid" => Some(Self::Euclid),
+            _ => None
+        }
+    }
+
+    // `q` and `r` are the truncated quotient and remainder of dividing by `v2`.
+    // A nonzero remainder keeps the adjustments below from overflowing.
+    fn quotient(self, q: i64, r: i64, v2: i64) -> i64 {
+        match self {
+            Self::Floor if r != 0 && (r < 0) != (v2 < 0) => q - 1,
+            Self::Euclid if r < 0 => if v2 > 0 { q - 1 } else { q + 1 },
+            _ => q
+        }
+    }
+
+    fn remainder(self, r: i64, v2: i64) -> i64 {
+        match self {
+            Self::Floor if r != 0 && (r < 0) != (v2 < 0) => r + v2,
+            Self::Euclid if r < 0 => if v2 > 0 { r + v2 } else { r - v2 },
+            _ => r
+        }
+    }
+}
+
+const OVERFLOW: &str = "Integer overflow error";
+const ZERO_DIVISION: &str = "Zero division error";
+
+impl Arithmetic {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "wrapping" => Some(Self::Wrapping),
+            "checked" => Some(Self::Checked),
+            "saturating" => Some(Self::Saturating),
+            _ => None
+        }
+    }
+
+    pub fn add(self, v1: i64, v2: i64) -> Result<i64, String> {
+        match self {
+            Self::Wrapping => Ok(v1.wrapping_add(v2)),
+            Self::Checked => v1.checked_add(v2).ok_or_else(|| OVERFLOW.to_string()),
+            Self::Saturating => Ok(v1.saturating_add(v2))
+        }
+    }
+
+    pub fn sub(self, v1: i64, v2: i64) -> Result<i64, String> {
+        match self {
+            Self::Wrapping => Ok(v1.wrapping_sub(v2)),
+            Self::Checked => v1.checked_sub(v2).ok_or_else(|| OVERFLOW.to_string()),
+            Self::Saturating => Ok(v1.saturating_sub(v2))
+        }
+    }
+
+    pub fn mul(self, v1: i64, v2: i64) -> Result<i64, String> {
+        match self {
+            Self::Wrapping => Ok(v1.wrapping_mul(v2)),
+            Self::Checked => v1.checked_mul(v2).ok_or_else(|| OVERFLOW.to_string()),
+            Self::Saturating => Ok(v1.saturating_mul(v2))
+        }
+    }
+
+    pub fn div(self, v1: i64, v2: i64, division: Division) -> Result<i64, String> {
+        if v2 == 0 {
+            return Err(ZERO_DIVISION.to_string());
+        }
+        let q = match self {
+            Self::Wrapping => v1.wrapping_div(v2),
+            Self::Checked => v1.checked_div(v2).ok_or_else(|| OVERFLOW.to_string())?,
+            Self::Saturating => v1.saturating_div(v2)
+        };
+        Ok(division.quotient(q, v1.wrapping_rem(v2), v2))
+    }
+
+    pub fn rem(self, v1: i64, v2: i64, division: Division) -> Result<i64, String> {
+        if v2 == 0 {
+            return Err(ZERO_DIVISION.to_string());
+        }
+        // i64::MIN % -1 is mathematically 0, which fits, so no policy treats it as an overflow.
+        Ok(division.remainder(v1.wrapping_rem(v2), v2))
+    }
+
+    pub fn neg(self, v: i64) -> Result<i64, String> {
+        match self {
+            Self::Wrapping => Ok(v.wrapping_neg()),
+            Self::Checked => v.checked_neg().ok_or_else(|| OVERFLOW.to_string()),
+            Self::Saturating => Ok(v.saturating_neg())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICIES: [Arithmetic; 3] = [Arithmetic::Wrapping, Arithmetic::Checked, Arithmetic::Saturating];
+
+    const MODES: [Division; 3] = [Division::Truncate, Division::Floor, Division::Euclid];
+
+    // Quotient and remainder of 7 / 3, -7 / 3, 7 / -3 and -7 / -3, as in the table on `Division`.
+    const SIGNS: [(i64, i64); 4] = [(7, 3), (-7, 3), (7, -3), (-7, -3)];
+
+    fn divmod(d: Division, a: i64, b: i64) -> (i64, i64) {
+        (Arithmetic::Checked.div(a, b, d).unwrap(), Arithmetic::Checked.rem(a, b, d).unwrap())
+    }
+
+    #[test]
+    fn truncate_signs() {
+        let found: Vec<_> = SIGNS.iter().map(|&(a, b)| divmod(Division::Truncate, a, b)).collect();
+        assert_eq!(found, [(2, 1), (-2, -1), (-2, 1), (2, -1)]);
+    }
+
+    #[test]
+    fn floor_signs() {
+        let found: Vec<_> = SIGNS.iter().map(|&(a, b)| divmod(Division::Floor, a, b)).collect();
+        assert_eq!(found, [(2, 1), (-3, 2), (-3, -2), (2, -1)]);
+    }
+
+    #[test]
+    fn euclid_signs() {
+        let found: Vec<_> = SIGNS.iter().map(|&(a, b)| divmod(Division::Euclid, a, b)).collect();
+        assert_eq!(found, [(2, 1), (-3, 2), (-2, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn quotient_and_remainder_agree() {
+        for d in MODES {
+            for a in -10..=10 {
+                for b in (-4..=4).filter(|&b| b != 0) {
+                    let (q, r) = divmod(d, a, b);
+                    assert_eq!(a, b * q + r, "{:?} {} {}", d, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn division_by_zero() {
+        for a in POLICIES {
+            for d in MODES {
+                assert_eq!(a.div(7, 0, d), Err(ZERO_DIVISION.to_string()), "{:?} {:?}", a, d);
+                assert_eq!(a.rem(7, 0, d), Err(ZERO_DIVISION.to_string()), "{:?} {:?}", a, d);
+            }
+        }
+    }
+
+    #[test]
+    fn min_divided_by_minus_one() {
+        for d in MODES {
+            assert_eq!(Arithmetic::Wrapping.div(i64::MIN, -1, d), Ok(i64::MIN), "{:?}", d);
+            assert_eq!(Arithmetic::Checked.div(i64::MIN, -1, d), Err(OVERFLOW.to_string()), "{:?}", d);
+            assert_eq!(Arithmetic::Saturating.div(i64::MIN, -1, d), Ok(i64::MAX), "{:?}", d);
+        }
+    }
+
+    #[test]
+    fn negating_min() {
+        assert_eq!(Arithmetic::Wrapping.neg(i64::MIN), Ok(i64::MIN));
+        assert_eq!(Arithmetic::Checked.neg(i64::MIN), Err(OVERFLOW.to_string()));
+        assert_eq!(Arithmetic::Saturating.neg(i64::MIN), Ok(i64::MAX));
+    }
+
+    #[test]
+    fn min_remainder_minus_one_is_zero() {
+        for a in POLICIES {
+            assert_eq!(a.rem(i64::MIN, -1, Division::Truncate), Ok(0), "{:?}", a);
+        }
+    }
+
+    #[test]
+    fn remainder_by_zero() {
+        for a in POLICIES {
+            assert_eq!(a.rem(7, 0, Division::Truncate), Err(ZERO_DIVISION.to_string()), "{:?}", a);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{checker::{Checker, Type}, environment::Environment, nodes::Expression, value::Value};
+
+/// Calls the host function or built-in function `name`; host functions
+/// registered under a built-in's name replace it. `divmod`, `sort`, `reverse` and
+/// `args()` return a tuple, which a destructuring such as `var (q, r) = divmod(x, y);` can unpack.
+///
+/// `args()` returns every script argument and `args(i)` the one at index `i`, or nil.
+/// Except for `divmod` and `args`, built-ins take any number of arguments and spread
+/// tuple arguments, so `sum(1, 2, 3)` and `sum((1, 2, 3))` agree.
+pub fn call(name: &str, args: &[Expression], env: &Environment) -> Result<Value, String> {
+    if let Some(f) = env.functions.get(name) {
+        let args = args.iter().map(|e| e.evaluate(env)).collect::<Result<Vec<_>, _>>()?;
+        return f.call(name, &args);
+    }
+    if name == "divmod" {
+        let [e1, e2] = args else {
+            return Err(format!("'{}' Argument count error", name));
+        };
+        let v1 = e1.int_operand(&e1.evaluate(env)?, env)?;
+        let v2 = e2.int_operand(&e2.evaluate(env)?, env)?;
+        return Ok(Value::Tuple(vec![
+            Value::Int(env.arithmetic.div(v1, v2, env.division)?),
+            Value::Int(env.arithmetic.rem(v1, v2, env.division)?)
+        ]));
+    }
+    if name == "args" {
+        return match args {
+            [] => Ok(Value::Tuple(env.args.clone())),
+            [e] => {
+                let i = e.int_operand(&e.evaluate(env)?, env)?;
+                Ok(usize::try_from(i).ok().and_then(|i| env.args.get(i)).cloned().into())
+            },
+            _ => Err(format!("'{}' Argument count error", name))
+        };
+    }
+
+    let mut values = vec![];
+    for e in args {
+        values.extend(e.evaluate_tuple(env)?);
+    }
+    match name {
+        "sum" => {
+            let mut total = 0;
+            for v in &values {
+                total = env.arithmetic.add(total, v.as_int(env.strict)?)?;
+            }
+            Ok(Value::Int(total))
+        },
+        "any" => Ok(Value::Bool(values.iter().any(Value::is_truthy))),
+        "all" => Ok(Value::Bool(values.iter().all(Value::is_truthy))),
+        "sort" => {
+            let mut ints = values.iter().map(|v| v.as_int(env.strict)).collect::<Result<Vec<_>, _>>()?;
+            ints.sort();
+            Ok(Value::Tuple(ints.into_iter().map(Value::Int).collect()))
+        },
+        "reverse" => {
+            values.reverse();
+            Ok(Value::Tuple(values))
+        },
+        _ => Err(format!("'{}' Undefined function error", name))
+    }
+}
+
+/// The type a call to the built-in `name` returns, or `None` when it is only known
+/// once the call runs or a host function has taken the name.
+pub fn return_type(name: &str, args: &[Expression], checker: &Checker) -> Option<Type> {
+    if checker.functions.contains(name) {
+        return None;
+    }
+    match (name, args.len()) {
+        ("divmod", 2) | ("sort" | "reverse", _) | ("args", 0) => Some(Type::Tuple),
+        ("sum", _) => Some(Type::Int),
+        ("any" | "all", _) => Some(Type::Bool),
+        _ => None
+    }
+}
+
+/// The types of the components a call to the built-in `name` returns, when their number is fixed.
+pub fn component_types(name: &str, args: &[Expression], checker: &Checker) -> Option<Vec<Option<Type>>> {
+    match (name, args.len()) {
+        ("divmod", 2) if !checker.functions.contains(name) => Some(vec![Some(Type::Int); 2]),
+        _ => None
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A type that can be written in an annotation such as `var n: int = 0;`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Int, Bool, Tuple, Range
+}
+
+impl Type {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "int" => Some(Self::Int),
+            "bool" => Some(Self::Bool),
+            "tuple" => Some(Self::Tuple),
+            "range" => Some(Self::Range),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int => write!(f, "int"),
+            Self::Bool => write!(f, "bool"),
+            Self::Tuple => write!(f, "tuple"),
+            Self::Range => write!(f, "range")
+        }
+    }
+}
+
+/// State of the type-checking pass that runs over the parsed program before it executes.
+/// Variables without an annotation map to `None` and are never reported.
+#[derive(Debug, Clone, Default)]
+pub struct Checker {
+    pub variables: HashMap<String, Option<Type>>,
+    /// Types of unannotated variables, taken from their initializers, that reads of them have.
+    pub inferred: HashMap<String, Type>,
+    /// Variables the program being checked assigns to, whose reads may have any type.
+    pub assigned: HashSet<String>,
+    /// Host functions, whose calls have no known type even under a built-in's name.
+    pub functions: HashSet<String>
+}
+
+impl Checker {
+    /// Records a new variable with its annotation, or with the type of its
+    /// initializer when it has none and the program never assigns to it.
+    pub fn define(&mut self, name: &str, annotation: Option<Type>, found: Option<Type>) {
+        self.variables.insert(name.to_string(), annotation);
+        match found {
+            Some(t) if annotation.is_none() && !self.assigned.contains(name) => self.inferred.insert(name.to_string(), t),
+            _ => self.inferred.remove(name)
+        };
+    }
+
+    /// The type reads of `name` are known to have, if any.
+    pub fn read_type(&self, name: &str) -> Option<Type> {
+        self.variables.get(name).copied().flatten().or_else(|| self.inferred.get(name).copied())
+    }
+
+    /// Reports a mismatch only when both the annotation and the value's type are known.
+    pub fn expect(name: &str, expected: Option<Type>, found: Option<Type>, line: u64, column: u64) -> Result<(), String> {
+        match (expected, found) {
+            (Some(e), Some(f)) if e != f => Err(format!(
+                "'{}' Type mismatch error, expected {} but found {} at line {} position {}", name, e, f, line, column
+            )),
+            _ => Ok(())
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::{cell::Cell, collections::HashMap};
+
+use crate::{arithmetic::{Arithmetic, Division}, cancel::CancelHandle, checker::Type, host::HostFunction, output::{Output, StdoutOutput}, value::Value};
+
+/// Why execution was stopped from outside the program rather than by one of its own errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Halt {
+    StepLimit,
+    Cancelled
+}
+
+/// Runtime state shared by every statement and expression of a program.
+#[derive(Debug)]
+pub struct Environment {
+    pub variables: HashMap<String, Value>,
+    /// Annotations of the variables defined with one, checked again whenever they are stored to.
+    pub annotations: HashMap<String, Type>,
+    pub functions: HashMap<String, HostFunction>,
+    /// Values passed to the script, read with `args()`.
+    pub args: Vec<Value>,
+    pub output: Box<dyn Output>,
+    pub arithmetic: Arithmetic,
+    pub division: Division,
+    /// Rejects booleans where an integer is expected instead of treating them as 0 and 1.
+    pub strict: bool,
+    /// Statements and expression nodes left to run, or `None` for no limit.
+    pub fuel: Cell<Option<u64>>,
+    pub halt: Cell<Option<Halt>>,
+    pub cancel: CancelHandle,
+    /// Approximate bytes held by the variables scripts have defined.
+    pub memory: usize,
+    pub peak_memory: usize,
+    pub memory_limit: Option<usize>
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            variables: HashMap::new(),
+            annotations: HashMap::new(),
+            functions: HashMap::new(),
+            args: vec![],
+            output: Box::new(StdoutOutput::new()),
+            arithmetic: Arithmetic::default(),
+            division: Division::default(),
+            strict: false,
+            fuel: Cell::new(None),
+            halt: Cell::new(None),
+            cancel: CancelHandle::default(),
+            memory: 0,
+            peak_memory: 0,
+            memory_limit: None
+        }
+    }
+}
+
+impl Environment {
+    /// Spends one step of fuel, failing once there is none left or the run was cancelled.
+    pub fn step(&self) -> Result<(), String> {
+        if self.cancel.is_cancelled() {
+            self.halt.set(Some(Halt::Cancelled));
+            return Err("Execution cancelled".to_string());
+        }
+        match self.fuel.get() {
+            Some(0) => {
+                self.halt.set(Some(Halt::StepLimit));
+                Err("Step limit exceeded".to_string())
+            }
+            Some(n) => {
+                self.fuel.set(Some(n - 1));
+                Ok(())
+            }
+            None => Ok(())
+        }
+    }
+
+    /// Stores a new variable, counting its name and value against the memory limit.
+    pub fn define(&mut self, name: &str, value: Value) -> Result<(), String> {
+        self.allocate(name, name.len() + value.size())?;
+        self.variables.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Replaces the value of a defined variable, counting any growth against the memory limit.
+    pub fn assign(&mut self, name: &str, value: Value) -> Result<(), String> {
+        let (old, new) = (self.variables.get(name).map_or(0, Value::size), value.size());
+        if new > old {
+            self.allocate(name, new - old)?;
+        }
+        else {
+            self.memory -= old - new;
+        }
+        self.variables.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn allocate(&mut self, name: &str, size: usize) -> Result<(), String> {
+        if self.memory_limit.is_some_and(|limit| self.memory + size > limit) {
+            return Err(format!("'{}' Out of memory error", name));
+        }
+        self.memory += size;
+        self.peak_memory = self.peak_memory.max(self.memory);
+        Ok(())
+    }
+}
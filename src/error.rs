@@ -0,0 +1,30 @@
+use std::fmt;
+
+/// Why a program could not be run to completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The source contains a character or literal the lexer cannot read.
+    Lexer { line: u64, position: u64 },
+    /// The tokens do not form a valid program.
+    Parser { line: u64, position: u64 },
+    /// The type-checking pass found a mismatch before anything ran.
+    Type(String),
+    /// Execution stopped with an error such as a zero division.
+    Runtime(String),
+    /// Execution used up its step limit before finishing.
+    StepLimit(String),
+    /// A `CancelHandle` stopped execution before it finished.
+    Cancelled(String)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Lexer { line, position } => write!(f, "Lexer scanning failed at line {} position {}", line, position),
+            Self::Parser { line, position } => write!(f, "Parser parsing failed at line {} position {}", line, position),
+            Self::Type(s) | Self::Runtime(s) | Self::StepLimit(s) | Self::Cancelled(s) => write!(f, "{}", s)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,26 @@
+use std::fmt;
+
+use crate::value::Value;
+
+type NativeFn = dyn Fn(&[Value]) -> Result<Value, String> + Send;
+
+/// A Rust closure registered by the embedder and callable from scripts by name.
+pub struct HostFunction {
+    pub arity: usize,
+    pub function: Box<NativeFn>
+}
+
+impl HostFunction {
+    pub fn call(&self, name: &str, args: &[Value]) -> Result<Value, String> {
+        if args.len() != self.arity {
+            return Err(format!("'{}' Argument count error", name));
+        }
+        (self.function)(args).map_err(|e| format!("'{}' Host function error: {}", name, e))
+    }
+}
+
+impl fmt::Debug for HostFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HostFunction").field("arity", &self.arity).finish_non_exhaustive()
+    }
+}
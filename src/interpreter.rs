@@ -0,0 +1,199 @@
+use crate::{
+    arithmetic::{Arithmetic, Division}, cancel::CancelHandle, checker::Checker, environment::{Environment, Halt}, error::Error, host::HostFunction,
+    lexer::Lexer, nodes::{self, Executable, Flow}, output::Output, parser::Parser, value::Value
+};
+
+/// Runs minimal programs. Variables, type annotations and settings persist
+/// from one call to the next, so a program can be fed in pieces.
+#[derive(Debug, Default)]
+pub struct Interpreter {
+    env: Environment,
+    checker: Checker,
+    step_limit: Option<u64>
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_arithmetic(&mut self, arithmetic: Arithmetic) {
+        self.env.arithmetic = arithmetic;
+    }
+
+    pub fn set_division(&mut self, division: Division) {
+        self.env.division = division;
+    }
+
+    /// Rejects booleans where an integer is expected instead of treating them as 0 and 1.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.env.strict = strict;
+    }
+
+    /// Stops every later `run_source` or `eval_expr` call once it has run `limit`
+    /// statements and expression nodes. `None`, the default, never stops.
+    pub fn set_step_limit(&mut self, limit: Option<u64>) {
+        self.step_limit = limit;
+    }
+
+    /// Fails a script with an out of memory error once the variables it
+    /// defines would take more than `limit` bytes. `None`, the default, never fails.
+    /// A variable costs its name's bytes plus its value, including every component of a tuple.
+    pub fn set_memory_limit(&mut self, limit: Option<usize>) {
+        self.env.memory_limit = limit;
+    }
+
+    /// The most memory, in bytes, the variables of the last run held at once.
+    pub fn peak_memory(&self) -> usize {
+        self.env.peak_memory
+    }
+
+    /// A handle that stops the current `run_source` or `eval_expr` call from another thread.
+    /// The flag is cleared when a call returns, so a cancel sent between calls stops the next one.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.env.cancel.clone()
+    }
+
+    /// The values scripts read with `args()`, or one at a time with `args(i)`.
+    pub fn set_args(&mut self, args: Vec<Value>) {
+        self.env.args = args;
+    }
+
+    /// Sends everything scripts print to `output` instead of standard output.
+    pub fn set_output(&mut self, output: impl Output + 'static) {
+        self.env.output = Box::new(output);
+    }
+
+    /// Type-checks and then runs every statement in `source`. Returns the status code
+    /// the program gave to `exit`, or `None` when it ran to its end.
+    pub fn run_source(&mut self, source: &str) -> Result<Option<u8>, Error> {
+        let result = self.run(source);
+        self.env.cancel.reset();
+        result
+    }
+
+    /// Evaluates a single expression such as `x * 2 + 1`, without a trailing semicolon.
+    pub fn eval_expr(&mut self, source: &str) -> Result<Value, Error> {
+        let result = self.evaluate(source);
+        self.env.cancel.reset();
+        result
+    }
+
+    /// Whether `source` is a whole program rather than the start of one, such as
+    /// an `if` block missing its closing brace. Invalid source counts as complete.
+    pub fn is_complete(source: &str) -> bool {
+        let Ok(lexer) = Self::scan(source) else {
+            return true;
+        };
+        let end = lexer.get_final_pos();
+        let mut parser = Parser::new(lexer.get_scanned_vec(), end);
+        parser.parse().err() != Some(end)
+    }
+
+    /// Forgets every variable and type annotation, keeping settings and registered functions.
+    pub fn reset(&mut self) {
+        self.env.variables.clear();
+        self.env.annotations.clear();
+        self.env.memory = 0;
+        self.checker = Checker {
+            functions: std::mem::take(&mut self.checker.functions),
+            ..Checker::default()
+        };
+    }
+
+    /// Every variable defined so far, in no particular order.
+    pub fn globals(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.env.variables.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<&Value> {
+        self.env.variables.get(name)
+    }
+
+    /// Defines `name` or overwrites its current value. A value that does not match
+    /// the type annotation `name` was defined with is rejected with a type error,
+    /// and the value counts against the memory limit like one a script stores.
+    pub fn set_global(&mut self, name: &str, value: Value) -> Result<(), Error> {
+        let expected = self.checker.variables.get(name).copied().flatten();
+        match (expected, value.type_of()) {
+            (Some(e), Some(f)) if e != f => {
+                return Err(Error::Type(format!("'{}' Type mismatch error, expected {} but found {}", name, e, f)));
+            }
+            _ => {}
+        }
+        // The host may store any type in an unannotated variable, so reads of it no longer have a known type.
+        self.checker.inferred.remove(name);
+        if self.env.variables.contains_key(name) {
+            self.env.assign(name, value)
+        }
+        else {
+            self.env.define(name, value)
+        }.map_err(Error::Runtime)
+    }
+
+    /// Makes `function` callable from scripts as `name(...)` with exactly `arity` arguments.
+    /// An `Err` it returns stops the script with a runtime error at the calling line.
+    pub fn register_function<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Value]) -> Result<Value, String> + Send + 'static
+    {
+        self.checker.functions.insert(name.to_string());
+        self.env.functions.insert(name.to_string(), HostFunction { arity, function: Box::new(function) });
+    }
+
+    fn run(&mut self, source: &str) -> Result<Option<u8>, Error> {
+        let statements = Self::parse(source)?;
+        // Check against a copy so a rejected program leaves no annotations behind.
+        let mut checker = self.checker.clone();
+        nodes::check_program(&statements, &mut checker).map_err(Error::Type)?;
+        self.checker = checker;
+        self.reset_limits();
+        let result = match nodes::execute_block(&statements, &mut self.env) {
+            Ok(Flow::Exit(code)) => Ok(Some(code)),
+            Ok(_) => Ok(None),
+            Err(s) => Err(self.runtime_error(s))
+        };
+        let flushed = self.env.output.flush().map_err(|e| Error::Runtime(format!("Output error ({})", e)));
+        let code = result?;
+        flushed?;
+        Ok(code)
+    }
+
+    fn evaluate(&mut self, source: &str) -> Result<Value, Error> {
+        let lexer = Self::scan(source)?;
+        let tokens = lexer.get_scanned_vec();
+        // Runtime errors, such as running out of steps, point at where the expression starts.
+        let (line, position) = tokens.first().map_or(lexer.get_final_pos(), |t| (t.1, t.2));
+        let mut parser = Parser::new(tokens, lexer.get_final_pos());
+        let expr = parser.parse_expression().map_err(|(line, position)| Error::Parser { line, position })?;
+        self.reset_limits();
+        expr.evaluate(&self.env).map_err(|s| self.runtime_error(format!("{} at line {} position {}", s, line, position)))
+    }
+
+    fn reset_limits(&mut self) {
+        self.env.fuel.set(self.step_limit);
+        self.env.halt.set(None);
+        self.env.peak_memory = self.env.memory;
+    }
+
+    fn runtime_error(&self, message: String) -> Error {
+        match self.env.halt.take() {
+            Some(Halt::StepLimit) => Error::StepLimit(message),
+            Some(Halt::Cancelled) => Error::Cancelled(message),
+            None => Error::Runtime(message)
+        }
+    }
+
+    fn scan(source: &str) -> Result<Lexer, Error> {
+        let mut lexer = Lexer::new(source.chars().collect());
+        lexer.scan().map_err(|(line, position)| Error::Lexer { line, position })?;
+        Ok(lexer)
+    }
+
+    fn parse(source: &str) -> Result<Vec<Box<dyn Executable>>, Error> {
+        let lexer = Self::scan(source)?;
+        let mut parser = Parser::new(lexer.get_scanned_vec(), lexer.get_final_pos());
+        parser.parse().map_err(|(line, position)| Error::Parser { line, position })?;
+        Ok(parser.parsed)
+    }
+}
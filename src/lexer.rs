@@ -1,3 +1,5 @@
+use unicode_ident::{is_xid_continue, is_xid_start};
+
 use crate::tokens::Tokens;
 
 pub struct Lexer {
@@ -42,32 +44,92 @@ impl Lexer {
     
     fn advance(&mut self) {
         self.position += 1;
+        self.line_pos += 1;
+    }
+
+    // Reads a decimal, `0x`, `0b` or `0o` literal, skipping `_` separators.
+    // Literals may reach 2^63 so that a minus sign in front can make i64::MIN;
+    // the parser rejects 2^63 anywhere else. Errors point at the first character of the literal.
+    fn scan_integer(&mut self) -> Result<u64, (u64, u64)> {
+        let start = (self.line, self.line_pos);
+        let radix = match (self.now(), self.peek()) {
+            ('0', Some('x' | 'X')) => 16,
+            ('0', Some('b' | 'B')) => 2,
+            ('0', Some('o' | 'O')) => 8,
+            _ => 10
+        };
+        if radix != 10 {
+            self.advance();
+            self.advance();
+        }
+        let mut temp_num: u64 = 0;
+        let mut digits = 0;
+        while is_xid_continue(self.now()) {
+            if self.now() != '_' {
+                let Some(d) = self.now().to_digit(radix) else {
+                    return Err(start);
+                };
+                temp_num = temp_num.checked_mul(radix as u64)
+                    .and_then(|n| n.checked_add(d as u64))
+                    .filter(|n| *n <= i64::MIN.unsigned_abs())
+                    .ok_or(start)?;
+                digits += 1;
+            }
+            self.advance();
+        }
+        if digits == 0 {
+            return Err(start);
+        }
+        Ok(temp_num)
+    }
+
+    // Reads a character literal such as 'a' or '\n' as its code point.
+    fn scan_char(&mut self) -> Result<u64, (u64, u64)> {
+        let start = (self.line, self.line_pos);
+        self.advance();
+        let c = match self.scanning.get(self.position) {
+            Some('\\') => {
+                self.advance();
+                match self.scanning.get(self.position) {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('0') => '\0',
+                    Some('\\') => '\\',
+                    Some('\'') => '\'',
+                    _ => return Err(start)
+                }
+            },
+            Some('\'' | '\n') | None => return Err(start),
+            Some(c) => *c
+        };
+        self.advance();
+        let Some('\'') = self.scanning.get(self.position) else {
+            return Err(start);
+        };
+        self.advance();
+        Ok(c as u64)
     }
 
     pub fn scan(&mut self) -> Result<(), (u64, u64)> {
         self.scanning.push(' ');
-        let mut temp_num: i64;
         let mut temp_str: String;
         while self.position < self.scanning.len() {
+            let (line, line_pos) = (self.line, self.line_pos);
             match self.now() {
                 '0'..='9' => {
-                    temp_num = 0;
-                    while self.now().is_numeric() {
-                        temp_num = temp_num.wrapping_mul(10i64) + self.now() as i64 - '0' as i64;
-                        self.advance();
-                    }
-                    self.scanned.push((Tokens::Integer(temp_num), self.line, self.line_pos));
+                    let temp_num = self.scan_integer()?;
+                    self.scanned.push((Tokens::Integer(temp_num), line, line_pos));
                 },
-                '_' | 'a'..='z' | 'A'..='Z' => {
+                '\'' => {
+                    let temp_num = self.scan_char()?;
+                    self.scanned.push((Tokens::Integer(temp_num), line, line_pos));
+                },
+                c if c == '_' || is_xid_start(c) => {
                     temp_str = String::new();
-                    loop {
-                        if let '_' | 'a'..='z' | 'A'..='Z' = self.now() {
-                            temp_str.push(self.now());
-                            self.advance();
-                        }
-                        else {
-                            break;
-                        }
+                    while is_xid_continue(self.now()) {
+                        temp_str.push(self.now());
+                        self.advance();
                     }
                     self.scanned.push((match &temp_str[..] {
                         "print" => Tokens::Print,
@@ -76,129 +138,171 @@ impl Lexer {
                         "if" => Tokens::If,
                         "else" => Tokens::Else,
                         "while" => Tokens::While,
+                        "loop" => Tokens::Loop,
+                        "do" => Tokens::Do,
+                        "until" => Tokens::Until,
+                        "break" => Tokens::Break,
+                        "continue" => Tokens::Continue,
+                        "in" => Tokens::In,
+                        "pragma" => Tokens::Pragma,
+                        "exit" => Tokens::Exit,
+                        "true" => Tokens::True,
+                        "false" => Tokens::False,
+                        "nil" => Tokens::Nil,
                         "or" => Tokens::LogicalOr,
                         "and" => Tokens::LogicalAnd,
                         _ => Tokens::Identifier(temp_str.clone())
-                    }, self.line, self.line_pos));
+                    }, line, line_pos));
                 },
                 '|' => {
                     if let Some('|') = self.peek() {
-                        self.scanned.push((Tokens::LogicalOr, self.line, self.line_pos));
+                        self.scanned.push((Tokens::LogicalOr, line, line_pos));
                         self.advance();
                         self.advance();
                     }
                     else { 
-                        self.scanned.push((Tokens::BitwiseOr, self.line, self.line_pos));
+                        self.scanned.push((Tokens::BitwiseOr, line, line_pos));
                         self.advance();
                     }
                 },
                 '&' => {
                     if let Some('&') = self.peek() {
-                        self.scanned.push((Tokens::LogicalAnd, self.line, self.line_pos));
+                        self.scanned.push((Tokens::LogicalAnd, line, line_pos));
                         self.advance();
                         self.advance();
                     }
                     else { 
-                        self.scanned.push((Tokens::BitwiseAnd, self.line, self.line_pos));
+                        self.scanned.push((Tokens::BitwiseAnd, line, line_pos));
                         self.advance();
                     }
                 },
+                '?' => {
+                    let Some('?') = self.peek() else {
+                        return Err((line, line_pos));
+                    };
+                    self.scanned.push((Tokens::QuestionQuestion, line, line_pos));
+                    self.advance();
+                    self.advance();
+                },
                 '^' => {
-                    self.scanned.push((Tokens::BitwiseXor, self.line, self.line_pos));
+                    self.scanned.push((Tokens::BitwiseXor, line, line_pos));
                     self.advance();
                 },
                 '>' => {
                     if let Some('=') = self.peek() {
-                        self.scanned.push((Tokens::GreaterEqual, self.line, self.line_pos));
+                        self.scanned.push((Tokens::GreaterEqual, line, line_pos));
                         self.advance();
                         self.advance();
                     }
                     else { 
-                        self.scanned.push((Tokens::Greater, self.line, self.line_pos));
+                        self.scanned.push((Tokens::Greater, line, line_pos));
                         self.advance();
                     }
                 },
                 '<' => {
                     if let Some('=') = self.peek() {
-                        self.scanned.push((Tokens::LesserEqual, self.line, self.line_pos));
+                        self.scanned.push((Tokens::LesserEqual, line, line_pos));
                         self.advance();
                         self.advance();
                     }
                     else { 
-                        self.scanned.push((Tokens::Lesser, self.line, self.line_pos));
+                        self.scanned.push((Tokens::Lesser, line, line_pos));
+                        self.advance();
+                    }
+                },
+                '.' => {
+                    let Some('.') = self.peek() else {
+                        return Err((line, line_pos));
+                    };
+                    self.advance();
+                    if let Some('=') = self.peek() {
+                        self.scanned.push((Tokens::DotDotEqual, line, line_pos));
+                        self.advance();
+                        self.advance();
+                    }
+                    else {
+                        self.scanned.push((Tokens::DotDot, line, line_pos));
                         self.advance();
                     }
                 },
                 '=' => {
                     if let Some('=') = self.peek() {
-                        self.scanned.push((Tokens::Equal, self.line, self.line_pos));
+                        self.scanned.push((Tokens::Equal, line, line_pos));
                         self.advance();
                         self.advance();
                     }
                     else { 
-                        self.scanned.push((Tokens::Assign, self.line, self.line_pos));
+                        self.scanned.push((Tokens::Assign, line, line_pos));
                         self.advance();
                     }
                 },
                 '!' => {
                     if let Some('=') = self.peek() {
-                        self.scanned.push((Tokens::NotEqual, self.line, self.line_pos));
+                        self.scanned.push((Tokens::NotEqual, line, line_pos));
                         self.advance();
                         self.advance();
                     }
                     else { 
-                        self.scanned.push((Tokens::LogicalNot, self.line, self.line_pos));
+                        self.scanned.push((Tokens::LogicalNot, line, line_pos));
                         self.advance();
                     }
                 },
                 '~' => {
-                    self.scanned.push((Tokens::BitwiseNot, self.line, self.line_pos));
+                    self.scanned.push((Tokens::BitwiseNot, line, line_pos));
                     self.advance();
                 },
                 '+' => {
-                    self.scanned.push((Tokens::Plus, self.line, self.line_pos));
+                    self.scanned.push((Tokens::Plus, line, line_pos));
                     self.advance();
                 },
                 '-' => {
-                    self.scanned.push((Tokens::Minus, self.line, self.line_pos));
+                    self.scanned.push((Tokens::Minus, line, line_pos));
                     self.advance();
                 },
                 '*' => {
-                    self.scanned.push((Tokens::Star, self.line, self.line_pos));
+                    self.scanned.push((Tokens::Star, line, line_pos));
                     self.advance();
                 },
                 '/' => {
-                    self.scanned.push((Tokens::Slash, self.line, self.line_pos));
+                    self.scanned.push((Tokens::Slash, line, line_pos));
                     self.advance();
                 },
                 '%' => {
-                    self.scanned.push((Tokens::Percent, self.line, self.line_pos));
+                    self.scanned.push((Tokens::Percent, line, line_pos));
                     self.advance();
                 },
                 '(' => {
-                    self.scanned.push((Tokens::LeftParen, self.line, self.line_pos));
+                    self.scanned.push((Tokens::LeftParen, line, line_pos));
                     self.advance();
                 },
                 ')' => {
-                    self.scanned.push((Tokens::RightParen, self.line, self.line_pos));
+                    self.scanned.push((Tokens::RightParen, line, line_pos));
                     self.advance();
                 },
                 '{' => {
-                    self.scanned.push((Tokens::LeftBrace, self.line, self.line_pos));
+                    self.scanned.push((Tokens::LeftBrace, line, line_pos));
                     self.advance();
                 },
                 '}' => {
-                    self.scanned.push((Tokens::RightBrace, self.line, self.line_pos));
+                    self.scanned.push((Tokens::RightBrace, line, line_pos));
+                    self.advance();
+                },
+                ',' => {
+                    self.scanned.push((Tokens::Comma, line, line_pos));
+                    self.advance();
+                },
+                ':' => {
+                    self.scanned.push((Tokens::Colon, line, line_pos));
                     self.advance();
                 },
                 ';' => {
-                    self.scanned.push((Tokens::SemiColon, self.line, self.line_pos));
+                    self.scanned.push((Tokens::SemiColon, line, line_pos));
                     self.advance();
                 }
                 '\n' => {
-                    self.line += 1;
-                    self.line_pos = 0;
                     self.advance();
+                    self.line += 1;
+                    self.line_pos = 1;
                 }
                 ' ' | '\t' | '\r' => {
                     self.advance();
@@ -207,7 +311,6 @@ impl Lexer {
                     return Err((self.line, self.line_pos)) 
                 }
             }
-            self.line_pos += 1;
         }
         Ok(())
     }
@@ -0,0 +1,21 @@
+mod tokens;
+mod lexer;
+mod nodes;
+mod parser;
+mod arithmetic;
+mod environment;
+mod checker;
+mod value;
+mod builtins;
+mod host;
+mod output;
+mod cancel;
+mod error;
+mod interpreter;
+
+pub use arithmetic::{Arithmetic, Division};
+pub use cancel::CancelHandle;
+pub use error::Error;
+pub use interpreter::Interpreter;
+pub use output::{BufferOutput, CallbackOutput, Output, StdoutOutput};
+pub use value::Value;
@@ -1,40 +1,186 @@
-use std::collections::HashMap;
+use std::{io::{self, Write}, process::ExitCode, thread, time::Duration};
 
-mod tokens;
-mod lexer;
-mod nodes;
-mod parser;
+use minimal::{Arithmetic, Error, Interpreter, Value};
 
-fn main() {
+mod repl;
+
+const USAGE: &str = "\
+Usage: minimal [options] [file | - | -e code] [-- arguments...]
+
+Without a file, minimal starts an interactive session. A file of - reads the
+program from standard input. Arguments after -- are integers or booleans
+that the script reads with args().
+
+Options:
+  -e <code>                Run <code> instead of a file
+  --arithmetic <mode>      Overflow behaviour: wrapping, checked or saturating
+  --strict                 Reject booleans where an integer is expected
+  --step-limit <steps>     Stop after running this many statements and expressions
+  --memory-limit <bytes>   Cap the memory held by variables and report its peak
+  --timeout <seconds>      Cancel the program after this long
+  --help                   Print this message
+  --version                Print the version
+
+Exit status:
+  0  success, or the code given to exit
+  1  runtime error
+  2  invalid command-line arguments
+  3  the program could not be read
+  4  lexer error
+  5  parser error
+  6  type error";
+
+const RUNTIME_FAILURE: u8 = 1;
+const USAGE_FAILURE: u8 = 2;
+const IO_FAILURE: u8 = 3;
+const LEXER_FAILURE: u8 = 4;
+const PARSER_FAILURE: u8 = 5;
+const TYPE_FAILURE: u8 = 6;
+
+enum Source {
+    File(String),
+    Stdin,
+    Inline(String)
+}
+
+fn main() -> ExitCode {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        println!("Source file required.");
-        return;
-    }
-    let mut lexer_: lexer::Lexer;
-    if let Ok(file) = std::fs::read_to_string(&args[1]) {
-        lexer_ = lexer::Lexer::new(file.chars().collect());
-    }
-    else {
-        println!("Cannot read the file.");
-        return;
+    let mut interpreter = Interpreter::new();
+    let mut source = None;
+    let mut memory_limit = None;
+    let mut timeout = None;
+    let mut i = 1;
+    while i < args.len() {
+        if args[i] == "--arithmetic" {
+            i += 1;
+            match args.get(i).and_then(|s| Arithmetic::from_name(s)) {
+                Some(a) => interpreter.set_arithmetic(a),
+                None => {
+                    eprintln!("--arithmetic expects wrapping, checked or saturating.");
+                    return ExitCode::from(USAGE_FAILURE);
+                }
+            }
+        }
+        else if args[i] == "--step-limit" {
+            i += 1;
+            match args.get(i).and_then(|s| s.parse().ok()) {
+                Some(n) => interpreter.set_step_limit(Some(n)),
+                None => {
+                    eprintln!("--step-limit expects a number of steps.");
+                    return ExitCode::from(USAGE_FAILURE);
+                }
+            }
+        }
+        else if args[i] == "--memory-limit" {
+            i += 1;
+            match args.get(i).and_then(|s| s.parse().ok()) {
+                Some(n) => memory_limit = Some(n),
+                None => {
+                    eprintln!("--memory-limit expects a number of bytes.");
+                    return ExitCode::from(USAGE_FAILURE);
+                }
+            }
+        }
+        else if args[i] == "--timeout" {
+            i += 1;
+            match args.get(i).and_then(|s| s.parse().ok()).and_then(|s| Duration::try_from_secs_f64(s).ok()) {
+                Some(d) => timeout = Some(d),
+                None => {
+                    eprintln!("--timeout expects a number of seconds.");
+                    return ExitCode::from(USAGE_FAILURE);
+                }
+            }
+        }
+        else if args[i] == "--strict" {
+            interpreter.set_strict(true);
+        }
+        else if args[i] == "--help" {
+            let _ = writeln!(io::stdout(), "{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        else if args[i] == "--version" {
+            println!("minimal {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        else if args[i] == "--" {
+            match args[i + 1..].iter().map(|s| s.parse()).collect::<Result<Vec<Value>, _>>() {
+                Ok(values) => interpreter.set_args(values),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return ExitCode::from(USAGE_FAILURE);
+                }
+            }
+            break;
+        }
+        else if source.is_some() {
+            eprintln!("Unexpected argument {}. Pass script arguments after --.", args[i]);
+            return ExitCode::from(USAGE_FAILURE);
+        }
+        else if args[i] == "-e" {
+            i += 1;
+            let Some(code) = args.get(i) else {
+                eprintln!("-e expects the code to run.");
+                return ExitCode::from(USAGE_FAILURE);
+            };
+            source = Some(Source::Inline(code.clone()));
+        }
+        else if args[i] == "-" {
+            source = Some(Source::Stdin);
+        }
+        else if args[i].starts_with('-') {
+            eprintln!("Unknown option {}. Use --help to list the options.", args[i]);
+            return ExitCode::from(USAGE_FAILURE);
+        }
+        else {
+            source = Some(Source::File(args[i].clone()));
+        }
+        i += 1;
     }
-    if let Err((l, p)) = lexer_.scan() {
-        println!("Lexer scanning failed at line {} position {}", l, p);
-        return;
+    interpreter.set_memory_limit(memory_limit);
+    let source = match source {
+        Some(Source::File(path)) => std::fs::read_to_string(path),
+        Some(Source::Stdin) => io::read_to_string(io::stdin()),
+        Some(Source::Inline(code)) => Ok(code),
+        None if timeout.is_some() => {
+            eprintln!("--timeout needs a program to run.");
+            return ExitCode::from(USAGE_FAILURE);
+        }
+        None => return repl::run(interpreter)
+    };
+    let Ok(mut source) = source else {
+        eprintln!("Cannot read the file.");
+        return ExitCode::from(IO_FAILURE);
+    };
+    // Blank out a `#!` line instead of removing it so line numbers stay the same.
+    if source.starts_with("#!") {
+        let end = source.find('\n').unwrap_or(source.len());
+        source.replace_range(..end, "");
     }
-    // println!("{:#?}", lexer_.scanned);
-    let mut parser_ = parser::Parser::new(lexer_.get_scanned_vec(), lexer_.get_final_pos());
-    if let Err((l, p)) = parser_.parse() {
-        println!("Parser parsing failed at line {} position {}", l, p);
+    if let Some(timeout) = timeout {
+        let handle = interpreter.cancel_handle();
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            handle.cancel();
+        });
     }
-    // println!("{:#?}", parser_.parsed);
-    let statements = parser_.parsed;
-    let mut variables = HashMap::new();
-    for s in &statements {
-        if let Err(e) = s.execute(&mut variables) {
-            println!("{}", e);
-            return;
-        }
+    let code = match interpreter.run_source(&source) {
+        Ok(code) => ExitCode::from(code.unwrap_or(0)),
+        Err(e) => report(&e)
+    };
+    if memory_limit.is_some() {
+        eprintln!("Peak memory usage: {} bytes", interpreter.peak_memory());
     }
+    code
+}
+
+/// Prints `e` and returns the status the process should end with.
+fn report(e: &Error) -> ExitCode {
+    let code = match e {
+        Error::Lexer { .. } => LEXER_FAILURE,
+        Error::Parser { .. } => PARSER_FAILURE,
+        Error::Type(_) => TYPE_FAILURE,
+        Error::Runtime(_) | Error::StepLimit(_) | Error::Cancelled(_) => RUNTIME_FAILURE
+    };
+    eprintln!("{}", e);
+    ExitCode::from(code)
 }
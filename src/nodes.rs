@@ -1,18 +1,25 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::{arithmetic::{Arithmetic, Division}, builtins, checker::{Checker, Type}, environment::Environment, value::Value};
 
 #[derive(Debug)]
 pub enum ExpressionType {
+    NullCoalescing,
     LogicalOr,
     LogicalAnd,
     BitwiseOr,
     BitwiseXor,
     BitwiseAnd,
     Equal, NotEqual,
+    In,
     Lesser, LesserEqual, Greater, GreaterEqual,
+    HalfOpenRange, InclusiveRange,
     Plus, Minus,
     Multiply, Divide, Remainder,
     LogicalNot, BitwiseNot, UnaryMinus,
-    Integer(i64), Variable(String), Parentheses(Box<Expression>)
+    Integer(i64), Boolean(bool), Nil, Variable(String), Parentheses(Box<Expression>),
+    Tuple(Vec<Expression>), Call(String, Vec<Expression>),
+    ComparisonChain(Vec<(ExpressionType, Expression)>)
 }
 
 #[derive(Debug)]
@@ -23,51 +30,203 @@ pub struct Expression {
 }
 
 impl Expression {
-    pub fn evaluate(&self, variables: &HashMap<&String, i64>) -> Result<i64, String> {
+    pub fn evaluate(&self, env: &Environment) -> Result<Value, String> {
         use ExpressionType::*;
 
-        if let Integer(_) | Variable(_) | Parentheses(_) = self.expression_type {
+        env.step()?;
+        if let Integer(_) | Boolean(_) | Nil | Variable(_) | Parentheses(_) = self.expression_type {
             return match &self.expression_type {
-                Integer(i) => Ok(*i),
-                Variable(s) => if let Some(i) = variables.get(&s) { Ok(*i) } else { Err(format!("'{}' Undefined variable error", s)) },
-                Parentheses(e) => e.evaluate(variables),
+                Integer(i) => Ok(Value::Int(*i)),
+                Boolean(b) => Ok(Value::Bool(*b)),
+                Nil => Ok(Value::Nil),
+                Variable(s) => if let Some(v) = env.variables.get(s) { Ok(v.clone()) } else { Err(format!("'{}' Undefined variable error", s)) },
+                Parentheses(e) => e.evaluate(env),
                 _ => Err("This should not happen".to_string())
             };
         }
 
+        if let Tuple(items) = &self.expression_type {
+            return Ok(Value::Tuple(items.iter().map(|e| e.evaluate(env)).collect::<Result<_, _>>()?));
+        }
+
+        if let Call(name, args) = &self.expression_type {
+            return builtins::call(name, args, env);
+        }
+
+        // `a ?? b` only evaluates `b` when `a` is nil.
+        if let NullCoalescing = self.expression_type {
+            return match self.value1.as_ref().unwrap().evaluate(env)? {
+                Value::Nil => self.value2.as_ref().unwrap().evaluate(env),
+                v => Ok(v)
+            };
+        }
+
+        // A range only holds its bounds; `in` checks against them without listing every integer.
+        if let HalfOpenRange | InclusiveRange = self.expression_type {
+            let (lo, hi) = (self.value1.as_ref().unwrap(), self.value2.as_ref().unwrap());
+            return Ok(Value::Range {
+                start: lo.int_operand(&lo.evaluate(env)?, env)?,
+                end: hi.int_operand(&hi.evaluate(env)?, env)?,
+                inclusive: matches!(self.expression_type, InclusiveRange)
+            });
+        }
+
+        if let In = self.expression_type {
+            let item = self.value1.as_ref().unwrap();
+            let v = item.evaluate(env)?;
+            return Ok(Value::Bool(self.value2.as_ref().unwrap().contains(item, &v, env)?));
+        }
+
+        // `a < b <= c` means `a < b && b <= c`, with `b` evaluated once.
+        if let ComparisonChain(rest) = &self.expression_type {
+            let first = self.value1.as_ref().unwrap();
+            let mut v1 = first.int_operand(&first.evaluate(env)?, env)?;
+            for (oper, e) in rest {
+                let v2 = e.int_operand(&e.evaluate(env)?, env)?;
+                let holds = match oper {
+                    Lesser => v1 < v2,
+                    LesserEqual => v1 <= v2,
+                    Greater => v1 > v2,
+                    GreaterEqual => v1 >= v2,
+                    _ => return Err("This should not happen".to_string())
+                };
+                if !holds {
+                    return Ok(Value::Bool(false));
+                }
+                v1 = v2;
+            }
+            return Ok(Value::Bool(true));
+        }
+
         if let LogicalNot | BitwiseNot | UnaryMinus = self.expression_type {
-            let v = self.value2.as_ref().unwrap().evaluate(variables)?;
+            let right = self.value2.as_ref().unwrap();
+            let v = right.evaluate(env)?;
             return match self.expression_type {
-                LogicalNot => if v == 0 { Ok(1) } else { Ok(0) },
-                BitwiseNot => Ok(!v),
-                UnaryMinus => Ok(-v),
+                LogicalNot => Ok(Value::Bool(!v.is_truthy())),
+                BitwiseNot => Ok(Value::Int(!right.int_operand(&v, env)?)),
+                UnaryMinus => Ok(Value::Int(env.arithmetic.neg(right.int_operand(&v, env)?)?)),
                 _ => Err("This should not happen".to_string())
             };
         }
 
-        let v1 = self.value1.as_ref().unwrap().evaluate(variables)?;
-        let v2 = self.value2.as_ref().unwrap().evaluate(variables)?;
+        let v1 = self.value1.as_ref().unwrap().evaluate(env)?;
+        let v2 = self.value2.as_ref().unwrap().evaluate(env)?;
         match self.expression_type {
-            LogicalOr => if v1 != 0 || v2 != 0 { Ok(1) } else { Ok(0) },
-            LogicalAnd => if v1 != 0 && v2 != 0 { Ok(1) } else { Ok(0) },
-            BitwiseOr => Ok(v1 | v2),
-            BitwiseXor => Ok(v1 ^ v2),
-            BitwiseAnd => Ok(v1 & v2),
-            Equal => if v1 == v2 { Ok(1) } else { Ok(0) },
-            NotEqual => if v1 != v2 { Ok(1) } else { Ok(0) },
-            Lesser => if v1 < v2 { Ok(1) } else { Ok(0) },
-            LesserEqual => if v1 <= v2 { Ok(1) } else { Ok(0) },
-            Greater => if v1 > v2 { Ok(1) } else { Ok(0) },
-            GreaterEqual => if v1 >= v2 { Ok(1) } else { Ok(0) },
-            Plus => Ok(v1.wrapping_add(v2)),
-            Minus => Ok(v1.wrapping_sub(v2)),
-            Multiply => Ok(v1.wrapping_mul(v2)),
-            Divide => if v2 == 0 { Err("Zero division error".to_string()) } else { Ok(v1.wrapping_div(v2)) },
-            Remainder => Ok(v1 % v2),
-            _ => Err("This should not happen".to_string())
+            LogicalOr => Ok(Value::Bool(v1.is_truthy() || v2.is_truthy())),
+            LogicalAnd => Ok(Value::Bool(v1.is_truthy() && v2.is_truthy())),
+            Equal => Ok(Value::Bool(v1 == v2)),
+            NotEqual => Ok(Value::Bool(v1 != v2)),
+            _ => {
+                let v1 = self.value1.as_ref().unwrap().int_operand(&v1, env)?;
+                let v2 = self.value2.as_ref().unwrap().int_operand(&v2, env)?;
+                Ok(match self.expression_type {
+                    BitwiseOr => Value::Int(v1 | v2),
+                    BitwiseXor => Value::Int(v1 ^ v2),
+                    BitwiseAnd => Value::Int(v1 & v2),
+                    Lesser => Value::Bool(v1 < v2),
+                    LesserEqual => Value::Bool(v1 <= v2),
+                    Greater => Value::Bool(v1 > v2),
+                    GreaterEqual => Value::Bool(v1 >= v2),
+                    Plus => Value::Int(env.arithmetic.add(v1, v2)?),
+                    Minus => Value::Int(env.arithmetic.sub(v1, v2)?),
+                    Multiply => Value::Int(env.arithmetic.mul(v1, v2)?),
+                    Divide => Value::Int(env.arithmetic.div(v1, v2, env.division)?),
+                    Remainder => Value::Int(env.arithmetic.rem(v1, v2, env.division)?),
+                    _ => return Err("This should not happen".to_string())
+                })
+            }
         }
     }
     
+    /// Evaluates to every component of a tuple, or to a single value otherwise.
+    /// Each component is evaluated before the caller stores any of them.
+    pub fn evaluate_tuple(&self, env: &Environment) -> Result<Vec<Value>, String> {
+        match self.evaluate(env)? {
+            Value::Tuple(v) => Ok(v),
+            v => Ok(vec![v])
+        }
+    }
+
+    /// The integer view of `v`, the value this operand evaluated to.
+    /// Using nil names the variable it came from.
+    pub fn int_operand(&self, v: &Value, env: &Environment) -> Result<i64, String> {
+        v.as_int(env.strict).map_err(|e| match (v, &self.expression_type) {
+            (Value::Nil, ExpressionType::Variable(s)) => format!("'{}' Nil value in arithmetic error", s),
+            (Value::Nil, ExpressionType::Parentheses(inner)) => inner.int_operand(v, env).unwrap_err(),
+            (Value::Nil, _) => "Nil value in arithmetic error".to_string(),
+            _ => e
+        })
+    }
+
+    /// Whether `v`, the value `item` evaluated to, is in this range or tuple.
+    /// A range compares `v` as an integer, so booleans count as 0 and 1 unless strict.
+    fn contains(&self, item: &Expression, v: &Value, env: &Environment) -> Result<bool, String> {
+        use ExpressionType::*;
+
+        match &self.expression_type {
+            Parentheses(e) => e.contains(item, v, env),
+            Tuple(items) => {
+                for e in items {
+                    if e.evaluate(env)? == *v {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            },
+            _ => match self.evaluate(env)? {
+                Value::Tuple(items) => Ok(items.contains(v)),
+                Value::Range { start, end, inclusive } => {
+                    let v = item.int_operand(v, env)?;
+                    Ok(start <= v && (v < end || inclusive && v == end))
+                },
+                _ => Err("'in' expects a range or tuple error".to_string())
+            }
+        }
+    }
+
+    /// The type this expression is known to have before running, if any.
+    pub fn static_type(&self, checker: &Checker) -> Option<Type> {
+        use ExpressionType::*;
+
+        match &self.expression_type {
+            Integer(_) => Some(Type::Int),
+            Boolean(_) => Some(Type::Bool),
+            Variable(s) => checker.read_type(s),
+            Parentheses(e) => e.static_type(checker),
+            // `nil ?? b` is always `b`; otherwise both sides must agree.
+            NullCoalescing => {
+                let (left, right) = (self.value1.as_ref().unwrap(), self.value2.as_ref().unwrap());
+                match (left.static_type(checker), right.static_type(checker)) {
+                    (_, r) if matches!(left.expression_type, Nil) => r,
+                    (Some(l), Some(r)) if l == r => Some(l),
+                    _ => None
+                }
+            },
+            Call(name, args) => builtins::return_type(name, args, checker),
+            LogicalOr | LogicalAnd | Equal | NotEqual | Lesser | LesserEqual | Greater | GreaterEqual | LogicalNot | ComparisonChain(_) | In => Some(Type::Bool),
+            BitwiseOr | BitwiseXor | BitwiseAnd | Plus | Minus | Multiply | Divide | Remainder | BitwiseNot | UnaryMinus => Some(Type::Int),
+            Tuple(_) => Some(Type::Tuple),
+            HalfOpenRange | InclusiveRange => Some(Type::Range),
+            Nil => None
+        }
+    }
+
+    /// The types of the components this expression evaluates to, when it is a tuple of known size.
+    pub fn component_types(&self, checker: &Checker) -> Option<Vec<Option<Type>>> {
+        use ExpressionType::*;
+
+        match &self.expression_type {
+            Tuple(v) => Some(v.iter().map(|e| e.static_type(checker)).collect()),
+            Parentheses(e) => e.component_types(checker),
+            Call(name, args) => builtins::component_types(name, args, checker),
+            _ => None
+        }
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        matches!(self.expression_type, ExpressionType::Tuple(_))
+    }
+
     pub fn new(left: Expression, operator: ExpressionType, right: Expression) -> Self {
         Self {
             expression_type: operator,
@@ -84,6 +243,14 @@ impl Expression {
         }
     }
     
+    pub fn new_chain(first: Expression, rest: Vec<(ExpressionType, Expression)>) -> Self {
+        Self {
+            expression_type: ExpressionType::ComparisonChain(rest),
+            value1: Some(Box::new(first)),
+            value2: None
+        }
+    }
+
     pub fn new_empty(operator: ExpressionType) -> Self {
         Self {
             expression_type: operator,
@@ -93,8 +260,61 @@ impl Expression {
     }
 }
 
+/// How control leaves a statement: normally, through `break`/`continue`, or by `exit`
+/// ending the whole program with a status code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flow {
+    Normal, Break, Continue, Exit(u8)
+}
+
 pub trait Executable: std::fmt::Debug {
-    fn execute<'a>(&'a self, variables: &mut HashMap<&'a String, i64>) -> Result<(), String>;
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String>;
+
+    /// The line the statement starts on, reported when execution is stopped there.
+    fn line(&self) -> u64;
+
+    /// Type-checks the statement before the program runs.
+    fn check(&self, _checker: &mut Checker) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Adds the variables the statement, or any statement nested in it, assigns to.
+    fn assigned(&self, _names: &mut HashSet<String>) {}
+}
+
+/// Type-checks a whole program. Variables it assigns to lose the type
+/// inferred from their initializer, since a read could see either value.
+pub fn check_program(statements: &[Box<dyn Executable>], checker: &mut Checker) -> Result<(), String> {
+    let mut names = HashSet::new();
+    assigned_in_block(statements, &mut names);
+    checker.inferred.retain(|name, _| !names.contains(name));
+    checker.assigned = names;
+    check_block(statements, checker)
+}
+
+pub fn check_block(statements: &[Box<dyn Executable>], checker: &mut Checker) -> Result<(), String> {
+    for s in statements {
+        s.check(checker)?;
+    }
+    Ok(())
+}
+
+pub fn assigned_in_block(statements: &[Box<dyn Executable>], names: &mut HashSet<String>) {
+    for s in statements {
+        s.assigned(names);
+    }
+}
+
+/// Runs statements in order, stopping early at the first `break`, `continue` or `exit`.
+pub fn execute_block(statements: &[Box<dyn Executable>], env: &mut Environment) -> Result<Flow, String> {
+    for s in statements {
+        env.step().map_err(|e| format!("{} at line {}", e, s.line()))?;
+        let flow = s.execute(env)?;
+        if flow != Flow::Normal {
+            return Ok(flow);
+        }
+    }
+    Ok(Flow::Normal)
 }
 
 #[derive(Debug)]
@@ -113,14 +333,19 @@ impl PrintStatement {
 }
 
 impl Executable for PrintStatement {
-    fn execute(&self, variables: &mut HashMap<&String, i64>) -> Result<(), String> {
-        if let Some(e) = &self.expression {
-            print!("{} ", e.evaluate(variables).or_else(|s| Err(format!("{} at line {}", s, self.line)))?);
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        let text = if let Some(e) = &self.expression {
+            format!("{} ", e.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?)
         }
         else {
-            print!(" ");
-        }
-        Ok(())
+            " ".to_string()
+        };
+        env.output.write_str(&text).map_err(|e| format!("Output error ({}) at line {}", e, self.line))?;
+        Ok(Flow::Normal)
     }
 }
 
@@ -140,41 +365,92 @@ impl PrintlnStatement {
 }
 
 impl Executable for PrintlnStatement {
-    fn execute(&self, variables: &mut HashMap<&String, i64>) -> Result<(), String> {
-        if let Some(e) = &self.expression {
-            println!("{}", e.evaluate(variables).or_else(|s| Err(format!("{} at line {}", s, self.line)))?);
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        let text = if let Some(e) = &self.expression {
+            format!("{}\n", e.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?)
         }
         else {
-            println!();
+            "\n".to_string()
+        };
+        env.output.write_str(&text).map_err(|e| format!("Output error ({}) at line {}", e, self.line))?;
+        Ok(Flow::Normal)
+    }
+}
+
+/// `f(x);` calls a function for its effect and drops the value it returns.
+#[derive(Debug)]
+pub struct CallStatement {
+    expression: Expression,
+    line: u64
+}
+
+impl CallStatement {
+    pub fn new(e: Expression, l: u64) -> Self {
+        Self {
+            expression: e,
+            line: l
         }
-        Ok(())
+    }
+}
+
+impl Executable for CallStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        self.expression.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?;
+        Ok(Flow::Normal)
     }
 }
 
 #[derive(Debug)]
 pub struct DefineStatement {
     variable_name: String,
+    type_annotation: Option<Type>,
     expression: Expression,
-    line: u64
+    line: u64,
+    column: u64
 }
 
 impl DefineStatement {
-    pub fn new(v: String, e: Expression, l: u64) -> Self {
+    pub fn new(v: String, t: Option<Type>, e: Expression, l: u64, c: u64) -> Self {
         Self {
             variable_name: v,
+            type_annotation: t,
             expression: e,
-            line: l
+            line: l,
+            column: c
         }
     }
 }
 
 impl Executable for DefineStatement {
-    fn execute<'a>(&'a self, variables: &mut HashMap<&'a String, i64>) -> Result<(), String> {
-        if let Some(_) = variables.get(&self.variable_name) {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        if env.variables.contains_key(&self.variable_name) {
             return Err(format!("'{}' Redefining variable error at line {}", &self.variable_name, self.line));
         }
-        let v = self.expression.evaluate(variables).or_else(|s| Err(format!("{} at line {}", s, self.line)))?;
-        variables.insert(&self.variable_name, v);
+        let v = self.expression.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?;
+        Checker::expect(&self.variable_name, self.type_annotation, v.type_of(), self.line, self.column)?;
+        env.define(&self.variable_name, v).map_err(|s| format!("{} at line {}", s, self.line))?;
+        if let Some(t) = self.type_annotation {
+            env.annotations.insert(self.variable_name.clone(), t);
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        let found = self.expression.static_type(checker);
+        Checker::expect(&self.variable_name, self.type_annotation, found, self.line, self.column)?;
+        checker.define(&self.variable_name, self.type_annotation, found);
         Ok(())
     }
 }
@@ -183,28 +459,177 @@ impl Executable for DefineStatement {
 pub struct AssignStatement {
     variable_name: String,
     expression: Expression,
-    line: u64
+    line: u64,
+    column: u64
 }
 
 impl AssignStatement {
-    pub fn new(v: String, e: Expression, l: u64) -> Self {
+    pub fn new(v: String, e: Expression, l: u64, c: u64) -> Self {
         Self {
             variable_name: v,
             expression: e,
-            line: l
+            line: l,
+            column: c
         }
     }
 }
 
 impl Executable for AssignStatement {
-    fn execute<'a >(&'a self, variables: &mut HashMap<&'a String, i64>) -> Result<(), String> {
-        if let None = variables.get(&self.variable_name) {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        if !env.variables.contains_key(&self.variable_name) {
             return Err(format!("'{}' Undefined variable error at line {}", &self.variable_name, self.line));
         }
-        let v = self.expression.evaluate(variables).or_else(|s| Err(format!("{} at line {}", s, self.line)))?;
-        variables.insert(&self.variable_name, v);
+        let v = self.expression.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?;
+        let expected = env.annotations.get(&self.variable_name).copied();
+        Checker::expect(&self.variable_name, expected, v.type_of(), self.line, self.column)?;
+        env.assign(&self.variable_name, v).map_err(|s| format!("{} at line {}", s, self.line))?;
+        Ok(Flow::Normal)
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        let expected = checker.variables.get(&self.variable_name).copied().flatten();
+        Checker::expect(&self.variable_name, expected, self.expression.static_type(checker), self.line, self.column)
+    }
+
+    fn assigned(&self, names: &mut HashSet<String>) {
+        names.insert(self.variable_name.clone());
+    }
+}
+
+/// `a, b = e1, e2;` or `var (a, b) = e;`: every value is computed before any is stored.
+#[derive(Debug)]
+pub struct DestructureStatement {
+    variable_names: Vec<String>,
+    type_annotations: Vec<Option<Type>>,
+    expression: Expression,
+    define: bool,
+    line: u64,
+    column: u64
+}
+
+impl DestructureStatement {
+    pub fn new(v: Vec<String>, t: Vec<Option<Type>>, e: Expression, d: bool, l: u64, c: u64) -> Self {
+        Self {
+            variable_names: v,
+            type_annotations: t,
+            expression: e,
+            define: d,
+            line: l,
+            column: c
+        }
+    }
+}
+
+impl Executable for DestructureStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        for (i, name) in self.variable_names.iter().enumerate() {
+            if self.define && (env.variables.contains_key(name) || self.variable_names[..i].contains(name)) {
+                return Err(format!("'{}' Redefining variable error at line {}", name, self.line));
+            }
+            if !self.define && !env.variables.contains_key(name) {
+                return Err(format!("'{}' Undefined variable error at line {}", name, self.line));
+            }
+        }
+        let values = self.expression.evaluate_tuple(env).map_err(|s| format!("{} at line {}", s, self.line))?;
+        if values.len() != self.variable_names.len() {
+            return Err(format!("Tuple size mismatch error at line {}", self.line));
+        }
+        for (i, (name, v)) in self.variable_names.iter().zip(&values).enumerate() {
+            let expected = if self.define {
+                self.type_annotations[i]
+            }
+            else {
+                env.annotations.get(name).copied()
+            };
+            Checker::expect(name, expected, v.type_of(), self.line, self.column)?;
+        }
+        for (name, v) in self.variable_names.iter().zip(values) {
+            if self.define {
+                env.define(name, v).map_err(|s| format!("{} at line {}", s, self.line))?;
+            }
+            else {
+                env.assign(name, v).map_err(|s| format!("{} at line {}", s, self.line))?;
+            }
+        }
+        if self.define {
+            for (name, t) in self.variable_names.iter().zip(&self.type_annotations) {
+                if let Some(t) = t {
+                    env.annotations.insert(name.clone(), *t);
+                }
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        let found = self.expression.component_types(checker).unwrap_or_default();
+        for (i, name) in self.variable_names.iter().enumerate() {
+            let expected = if self.define {
+                self.type_annotations[i]
+            }
+            else {
+                checker.variables.get(name).copied().flatten()
+            };
+            Checker::expect(name, expected, found.get(i).copied().flatten(), self.line, self.column)?;
+        }
+        if self.define {
+            for (i, (name, t)) in self.variable_names.iter().zip(&self.type_annotations).enumerate() {
+                checker.define(name, *t, found.get(i).copied().flatten());
+            }
+        }
         Ok(())
     }
+
+    fn assigned(&self, names: &mut HashSet<String>) {
+        if !self.define {
+            names.extend(self.variable_names.iter().cloned());
+        }
+    }
+}
+
+/// A setting a program can change from where the pragma runs onward.
+#[derive(Debug, Clone, Copy)]
+pub enum Pragma {
+    Arithmetic(Arithmetic),
+    Division(Division)
+}
+
+/// `pragma division floor;` or `pragma arithmetic checked;`
+#[derive(Debug)]
+pub struct PragmaStatement {
+    pragma: Pragma,
+    line: u64
+}
+
+impl PragmaStatement {
+    pub fn new(p: Pragma, l: u64) -> Self {
+        Self {
+            pragma: p,
+            line: l
+        }
+    }
+}
+
+impl Executable for PragmaStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        match self.pragma {
+            Pragma::Arithmetic(a) => env.arithmetic = a,
+            Pragma::Division(d) => env.division = d
+        }
+        Ok(Flow::Normal)
+    }
 }
 
 #[derive(Debug)]
@@ -227,18 +652,27 @@ impl IfStatement {
 }
 
 impl Executable for IfStatement {
-    fn execute<'a>(&'a self, variables: &mut HashMap<&'a String, i64>) -> Result<(), String> {
-        if self.condition.evaluate(variables).or_else(|s| Err(format!("{} at line {}", s, self.line)))? != 0 {
-            for s in &self.statements {
-                let _ = s.execute(variables)?;
-            }
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        if self.condition.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?.is_truthy() {
+            execute_block(&self.statements, env)
         }
         else {
-            for s in &self.else_statements {
-                let _ = s.execute(variables)?;
-            }
+            execute_block(&self.else_statements, env)
         }
-        Ok(())
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        check_block(&self.statements, checker)?;
+        check_block(&self.else_statements, checker)
+    }
+
+    fn assigned(&self, names: &mut HashSet<String>) {
+        assigned_in_block(&self.statements, names);
+        assigned_in_block(&self.else_statements, names);
     }
 }
 
@@ -260,12 +694,230 @@ impl WhileStatement {
 }
 
 impl Executable for WhileStatement {
-    fn execute<'a>(&'a self, variables: &mut HashMap<&'a String, i64>) -> Result<(), String> {
-        while self.condition.evaluate(variables).or_else(|s| Err(format!("{} at line {}", s, self.line)))? != 0 {
-            for s in &self.statements {
-                let _ = s.execute(variables)?;
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        while self.condition.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?.is_truthy() {
+            match execute_block(&self.statements, env)? {
+                Flow::Break => break,
+                Flow::Exit(code) => return Ok(Flow::Exit(code)),
+                _ => {}
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        check_block(&self.statements, checker)
+    }
+
+    fn assigned(&self, names: &mut HashSet<String>) {
+        assigned_in_block(&self.statements, names);
+    }
+}
+
+#[derive(Debug)]
+pub struct UntilStatement {
+    condition: Expression,
+    statements: Vec<Box<dyn Executable>>,
+    line: u64
+}
+
+impl UntilStatement {
+    pub fn new(c: Expression, s: Vec<Box<dyn Executable>>, l: u64) -> Self {
+        Self {
+            condition: c,
+            statements: s,
+            line: l
+        }
+    }
+}
+
+impl Executable for UntilStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        while !self.condition.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?.is_truthy() {
+            match execute_block(&self.statements, env)? {
+                Flow::Break => break,
+                Flow::Exit(code) => return Ok(Flow::Exit(code)),
+                _ => {}
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        check_block(&self.statements, checker)
+    }
+
+    fn assigned(&self, names: &mut HashSet<String>) {
+        assigned_in_block(&self.statements, names);
+    }
+}
+
+#[derive(Debug)]
+pub struct DoWhileStatement {
+    statements: Vec<Box<dyn Executable>>,
+    condition: Expression,
+    line: u64
+}
+
+impl DoWhileStatement {
+    pub fn new(s: Vec<Box<dyn Executable>>, c: Expression, l: u64) -> Self {
+        Self {
+            statements: s,
+            condition: c,
+            line: l
+        }
+    }
+}
+
+impl Executable for DoWhileStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        loop {
+            match execute_block(&self.statements, env)? {
+                Flow::Break => break,
+                Flow::Exit(code) => return Ok(Flow::Exit(code)),
+                _ => {}
+            }
+            if !self.condition.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?.is_truthy() {
+                break;
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        check_block(&self.statements, checker)
+    }
+
+    fn assigned(&self, names: &mut HashSet<String>) {
+        assigned_in_block(&self.statements, names);
+    }
+}
+
+#[derive(Debug)]
+pub struct LoopStatement {
+    statements: Vec<Box<dyn Executable>>,
+    line: u64
+}
+
+impl LoopStatement {
+    pub fn new(s: Vec<Box<dyn Executable>>, l: u64) -> Self {
+        Self {
+            statements: s,
+            line: l
+        }
+    }
+}
+
+impl Executable for LoopStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        // An empty body spends no fuel of its own, so each pass costs a step.
+        loop {
+            env.step().map_err(|e| format!("{} at line {}", e, self.line))?;
+            match execute_block(&self.statements, env)? {
+                Flow::Break => break,
+                Flow::Exit(code) => return Ok(Flow::Exit(code)),
+                _ => {}
             }
         }
-        Ok(())    
+        Ok(Flow::Normal)
+    }
+
+    fn check(&self, checker: &mut Checker) -> Result<(), String> {
+        check_block(&self.statements, checker)
+    }
+
+    fn assigned(&self, names: &mut HashSet<String>) {
+        assigned_in_block(&self.statements, names);
+    }
+}
+
+/// `exit 1;` stops the program with a status code from 0 to 255.
+#[derive(Debug)]
+pub struct ExitStatement {
+    expression: Expression,
+    line: u64
+}
+
+impl ExitStatement {
+    pub fn new(e: Expression, l: u64) -> Self {
+        Self {
+            expression: e,
+            line: l
+        }
+    }
+}
+
+impl Executable for ExitStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, env: &mut Environment) -> Result<Flow, String> {
+        let v = self.expression.evaluate(env).map_err(|s| format!("{} at line {}", s, self.line))?;
+        let code = self.expression.int_operand(&v, env).map_err(|s| format!("{} at line {}", s, self.line))?;
+        let code = u8::try_from(code).map_err(|_| format!("'{}' Exit code out of range error at line {}", code, self.line))?;
+        Ok(Flow::Exit(code))
+    }
+}
+
+#[derive(Debug)]
+pub struct BreakStatement {
+    line: u64
+}
+
+impl BreakStatement {
+    pub fn new(l: u64) -> Self {
+        Self {
+            line: l
+        }
+    }
+}
+
+impl Executable for BreakStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, _env: &mut Environment) -> Result<Flow, String> {
+        Ok(Flow::Break)
+    }
+}
+
+#[derive(Debug)]
+pub struct ContinueStatement {
+    line: u64
+}
+
+impl ContinueStatement {
+    pub fn new(l: u64) -> Self {
+        Self {
+            line: l
+        }
+    }
+}
+
+impl Executable for ContinueStatement {
+    fn line(&self) -> u64 {
+        self.line
+    }
+
+    fn execute(&self, _env: &mut Environment) -> Result<Flow, String> {
+        Ok(Flow::Continue)
     }
 }
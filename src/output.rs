@@ -0,0 +1,106 @@
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+
+/// Where `print` and `println` send their text.
+pub trait Output: Send {
+    fn write_str(&mut self, s: &str) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl fmt::Debug for dyn Output {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Output")
+    }
+}
+
+/// Writes to the process's standard output. This is the default.
+///
+/// Text is buffered and written out at every newline when stdout is a
+/// terminal, and only when the buffer fills up or the run ends otherwise.
+#[derive(Debug)]
+pub struct StdoutOutput {
+    buffer: String,
+    line_buffered: bool
+}
+
+const STDOUT_BUFFER_SIZE: usize = 8192;
+
+impl StdoutOutput {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::with_capacity(STDOUT_BUFFER_SIZE),
+            line_buffered: io::stdout().is_terminal()
+        }
+    }
+}
+
+impl Default for StdoutOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Output for StdoutOutput {
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.buffer.push_str(s);
+        if self.buffer.len() >= STDOUT_BUFFER_SIZE || self.line_buffered && s.contains('\n') {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        // Empty the buffer even on failure so a broken pipe is reported once.
+        let written = stdout.write_all(self.buffer.as_bytes());
+        self.buffer.clear();
+        written?;
+        stdout.flush()
+    }
+}
+
+impl Drop for StdoutOutput {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Collects output in memory. Clones share the same text, so keep one to read
+/// what a script printed after handing another to the interpreter.
+#[derive(Debug, Clone, Default)]
+pub struct BufferOutput(Arc<Mutex<String>>);
+
+impl BufferOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contents(&self) -> String {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns the text collected so far and empties the buffer.
+    pub fn take(&self) -> String {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Output for BufferOutput {
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).push_str(s);
+        Ok(())
+    }
+}
+
+/// Hands every piece of output to a closure.
+pub struct CallbackOutput<F>(pub F);
+
+impl<F: FnMut(&str) -> io::Result<()> + Send> Output for CallbackOutput<F> {
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        (self.0)(s)
+    }
+}
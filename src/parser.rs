@@ -1,10 +1,14 @@
-use crate::{tokens::Tokens, tokens::Tokens::*, nodes::*};
+use crate::{tokens::Tokens, tokens::Tokens::*, nodes::{self, *}, checker::Type, arithmetic::{Arithmetic, Division}};
+
+// A variable name and its optional type annotation.
+type Binding = (String, Option<Type>);
 
 pub struct Parser {
     parsing: Vec<(Tokens, u64, u64)>,
     pub parsed: Vec<Box<dyn Executable>>,
     position: usize,
-    end_pos: (u64, u64)
+    end_pos: (u64, u64),
+    loop_depth: usize
 }
 
 impl Parser {
@@ -13,20 +17,26 @@ impl Parser {
             parsing: v,
             parsed: vec![],
             position: 0,
-            end_pos: p
+            end_pos: p,
+            loop_depth: 0
         }
     }
 
+    // Past the last token, `now` is `EndOfFile` and the position is the end of the source.
     fn now(&self) -> Tokens {
-        self.parsing[self.position].0.clone()
+        self.parsing.get(self.position).map_or(EndOfFile, |t| t.0.clone())
+    }
+
+    fn peek(&self) -> Tokens {
+        self.parsing.get(self.position + 1).map_or(EndOfFile, |t| t.0.clone())
     }
 
     fn now_line(&self) -> u64 {
-        self.parsing[self.position].1
+        self.now_pos().0
     }
 
     fn now_pos(&self) -> (u64, u64) {
-        (self.parsing[self.position].1, self.parsing[self.position].2)
+        self.parsing.get(self.position).map_or(self.end_pos, |t| (t.1, t.2))
     }
 
     fn advance(&mut self) {
@@ -52,14 +62,31 @@ impl Parser {
         Ok(())
     }
 
+    /// Parses the whole input as one expression, with no trailing semicolon.
+    pub fn parse_expression(&mut self) -> Result<Expression, (u64, u64)> {
+        self.eof_error_check()?;
+        let expr = self.parse_null_coalescing()?;
+        if !self.is_end() {
+            return Err(self.now_pos());
+        }
+        Ok(expr)
+    }
+
     fn parse_statement(&mut self) -> Result<Box<dyn Executable>, (u64, u64)> {
         match self.now() {
             Print => Ok(self.parse_print()?),
             Println => Ok(self.parse_println()?),
             Var => Ok(self.parse_define()?),
+            Identifier(_) if self.peek() == LeftParen => Ok(self.parse_call()?),
             Identifier(_) => Ok(self.parse_assign()?),
             If => Ok(self.parse_if()?),
             While => Ok(self.parse_while()?),
+            Until => Ok(self.parse_until()?),
+            Do => Ok(self.parse_do_while()?),
+            Loop => Ok(self.parse_loop()?),
+            Break | Continue => self.parse_break_continue(),
+            Tokens::Pragma => Ok(self.parse_pragma()?),
+            Exit => Ok(self.parse_exit()?),
             _ => Err(self.now_pos())
         }
     }
@@ -67,13 +94,13 @@ impl Parser {
     fn parse_print(&mut self) -> Result<Box<PrintStatement>, (u64, u64)> {
         let l = self.now_line();
         self.advance();
-        let _ = self.eof_error_check()?;
+        self.eof_error_check()?;
         if let SemiColon = self.now() {
             self.advance();
             Ok(Box::new(PrintStatement::new(None, l)))
         }
         else {
-            let expr = self.parse_logical_or()?;
+            let expr = self.parse_null_coalescing()?;
             if let SemiColon = self.now() {
                 self.advance();
                 Ok(Box::new(PrintStatement::new(Some(expr), l)))
@@ -87,13 +114,13 @@ impl Parser {
     fn parse_println(&mut self) -> Result<Box<PrintlnStatement>, (u64, u64)> {
         let l = self.now_line();
         self.advance();
-        let _ = self.eof_error_check()?;
+        self.eof_error_check()?;
         if let SemiColon = self.now() {
             self.advance();
             Ok(Box::new(PrintlnStatement::new(None, l)))
         }
         else {
-            let expr = self.parse_logical_or()?;
+            let expr = self.parse_null_coalescing()?;
             if let SemiColon = self.now() {
                 self.advance();
                 Ok(Box::new(PrintlnStatement::new(Some(expr), l)))
@@ -104,96 +131,191 @@ impl Parser {
         }
     }
 
-    fn parse_define(&mut self) -> Result<Box<DefineStatement>, (u64, u64)> {
+    fn parse_define(&mut self) -> Result<Box<dyn Executable>, (u64, u64)> {
         let l = self.now_line();
-        let (iden, expr);
         self.advance();
-        let _ = self.eof_error_check()?;
-        if let Identifier(i) = self.now() {
-            iden = i;
+        self.eof_error_check()?;
+        let bindings = if let LeftParen = self.now() {
+            self.advance();
+            let bindings = self.parse_identifier_list(true)?;
+            self.eof_error_check()?;
+            let RightParen = self.now() else {
+                return Err(self.now_pos());
+            };
+            self.advance();
+            bindings
         }
         else {
+            self.parse_identifier_list(true)?
+        };
+        self.eof_error_check()?;
+        let Assign = self.now() else {
             return Err(self.now_pos());
-        }
+        };
         self.advance();
-        let _ = self.eof_error_check()?;
-        let Assign = self.now() else {
+        self.eof_error_check()?;
+        let c = self.now_pos().1;
+        let expr = self.parse_expression_list()?;
+        self.eof_error_check()?;
+        let SemiColon = self.now() else {
             return Err(self.now_pos());
         };
         self.advance();
-        let _ = self.eof_error_check()?;
-        expr = self.parse_logical_or()?;
-        if let SemiColon = self.now() {
-            self.advance();
-            Ok(Box::new(DefineStatement::new(iden, expr, l)))
+        let (mut names, mut types): (Vec<_>, Vec<_>) = bindings.into_iter().unzip();
+        if names.len() == 1 {
+            Ok(Box::new(DefineStatement::new(names.remove(0), types.remove(0), expr, l, c)))
         }
         else {
-            Err(self.now_pos())
+            Ok(Box::new(DestructureStatement::new(names, types, expr, true, l, c)))
         }
     }
 
-    fn parse_assign(&mut self) -> Result<Box<AssignStatement>, (u64, u64)> {
+    fn parse_assign(&mut self) -> Result<Box<dyn Executable>, (u64, u64)> {
         let l = self.now_line();
-        let iden = if let Identifier(i) = self.now() {
-            i
-        }
-        else {
+        let (mut names, types): (Vec<_>, Vec<_>) = self.parse_identifier_list(false)?.into_iter().unzip();
+        self.eof_error_check()?;
+        let Assign = self.now() else {
             return Err(self.now_pos());
         };
         self.advance();
-        let _ = self.eof_error_check()?;
-        let Assign = self.now() else {
+        self.eof_error_check()?;
+        let c = self.now_pos().1;
+        let expr = self.parse_expression_list()?;
+        self.eof_error_check()?;
+        let SemiColon = self.now() else {
             return Err(self.now_pos());
         };
         self.advance();
-        let _ = self.eof_error_check()?;
-        let expr = self.parse_logical_or()?;
-        if let SemiColon = self.now() {
-            self.advance();
-            Ok(Box::new(AssignStatement::new(iden, expr, l)))
+        if names.len() == 1 {
+            Ok(Box::new(AssignStatement::new(names.remove(0), expr, l, c)))
         }
         else {
-            Err(self.now_pos())
+            Ok(Box::new(DestructureStatement::new(names, types, expr, false, l, c)))
         }
     }
-    
-    fn parse_if(&mut self) -> Result<Box<IfStatement>, (u64, u64)> {
+
+    // Parses a call such as `log(x);` run for its effect; the value it returns is dropped.
+    fn parse_call(&mut self) -> Result<Box<CallStatement>, (u64, u64)> {
+        let l = self.now_line();
+        let expr = self.parse_primary()?;
+        self.eof_error_check()?;
+        let SemiColon = self.now() else {
+            return Err(self.now_pos());
+        };
+        self.advance();
+        Ok(Box::new(CallStatement::new(expr, l)))
+    }
+
+    // Parses `a` or `a, b, c`; with `annotated`, each name may be followed by `: type`.
+    fn parse_identifier_list(&mut self, annotated: bool) -> Result<Vec<Binding>, (u64, u64)> {
+        let mut bindings = vec![];
+        loop {
+            self.eof_error_check()?;
+            let Identifier(i) = self.now() else {
+                return Err(self.now_pos());
+            };
+            self.advance();
+            self.eof_error_check()?;
+            let mut t = None;
+            if annotated && self.now() == Colon {
+                self.advance();
+                self.eof_error_check()?;
+                let Identifier(type_name) = self.now() else {
+                    return Err(self.now_pos());
+                };
+                t = Some(Type::from_name(&type_name).ok_or(self.now_pos())?);
+                self.advance();
+                self.eof_error_check()?;
+            }
+            bindings.push((i, t));
+            let Comma = self.now() else {
+                break;
+            };
+            self.advance();
+        }
+        Ok(bindings)
+    }
+
+    // Parses `e` or `e1, e2, e3`, the latter as a tuple.
+    fn parse_expression_list(&mut self) -> Result<Expression, (u64, u64)> {
+        let expr = self.parse_null_coalescing()?;
+        if self.is_end() || self.now() != Comma {
+            return Ok(expr);
+        }
+        let mut exprs = vec![expr];
+        while !self.is_end() && self.now() == Comma {
+            self.advance();
+            exprs.push(self.parse_null_coalescing()?);
+        }
+        Ok(Expression::new_empty(ExpressionType::Tuple(exprs)))
+    }
+
+    fn parse_pragma(&mut self) -> Result<Box<PragmaStatement>, (u64, u64)> {
         let l = self.now_line();
         self.advance();
-        let _ = self.eof_error_check()?;
-        let cond = self.parse_logical_or()?;
+        self.eof_error_check()?;
+        let Identifier(name) = self.now() else {
+            return Err(self.now_pos());
+        };
+        self.advance();
+        self.eof_error_check()?;
+        let Identifier(setting) = self.now() else {
+            return Err(self.now_pos());
+        };
+        let pragma = match &name[..] {
+            "arithmetic" => Arithmetic::from_name(&setting).map(nodes::Pragma::Arithmetic),
+            "division" => Division::from_name(&setting).map(nodes::Pragma::Division),
+            _ => None
+        }.ok_or(self.now_pos())?;
+        self.advance();
+        self.eof_error_check()?;
+        let SemiColon = self.now() else {
+            return Err(self.now_pos());
+        };
+        self.advance();
+        Ok(Box::new(PragmaStatement::new(pragma, l)))
+    }
+
+    // Parses `{ statements }`, leaving the position after the closing brace.
+    fn parse_block(&mut self) -> Result<Vec<Box<dyn Executable>>, (u64, u64)> {
+        self.eof_error_check()?;
         let LeftBrace = self.now() else {
             return Err(self.now_pos());
         };
         self.advance();
-        let _ = self.eof_error_check()?;
-        let mut if_stmts = vec![];
+        self.eof_error_check()?;
+        let mut stmts = vec![];
         loop {
             if let RightBrace = self.now() {
                 break;
             }
-            if_stmts.push(self.parse_statement()?);
+            stmts.push(self.parse_statement()?);
+            self.eof_error_check()?;
         }
         self.advance();
+        Ok(stmts)
+    }
+
+    // Parses the body of a loop, where `break` and `continue` are allowed.
+    fn parse_loop_block(&mut self) -> Result<Vec<Box<dyn Executable>>, (u64, u64)> {
+        self.loop_depth += 1;
+        let stmts = self.parse_block();
+        self.loop_depth -= 1;
+        stmts
+    }
+
+    fn parse_if(&mut self) -> Result<Box<IfStatement>, (u64, u64)> {
+        let l = self.now_line();
+        self.advance();
+        self.eof_error_check()?;
+        let cond = self.parse_null_coalescing()?;
+        let if_stmts = self.parse_block()?;
         if self.is_end() {
             return Ok(Box::new(IfStatement::new(cond, if_stmts, vec![], l)));
         }
         if let Else = self.now() {
             self.advance();
-            let _ = self.eof_error_check()?;
-            let LeftBrace = self.now() else {
-                return Err(self.now_pos());  
-            };
-            self.advance();
-            let _ = self.eof_error_check()?;
-            let mut else_stmts = vec![];
-            loop {
-                if let RightBrace = self.now() {
-                    break;
-                }
-                else_stmts.push(self.parse_statement()?);
-            }
-            self.advance();
+            let else_stmts = self.parse_block()?;
             Ok(Box::new(IfStatement::new(cond, if_stmts, else_stmts, l)))
         }
         else {
@@ -204,29 +326,98 @@ impl Parser {
     fn parse_while(&mut self) -> Result<Box<WhileStatement>, (u64, u64)> {
         let l = self.now_line();
         self.advance();
-        let _ = self.eof_error_check()?;
-        let cond = self.parse_logical_or()?;
-        let LeftBrace = self.now() else {
+        self.eof_error_check()?;
+        let cond = self.parse_null_coalescing()?;
+        let while_stmts = self.parse_loop_block()?;
+        Ok(Box::new(WhileStatement::new(cond, while_stmts, l)))
+    }
+
+    fn parse_until(&mut self) -> Result<Box<UntilStatement>, (u64, u64)> {
+        let l = self.now_line();
+        self.advance();
+        self.eof_error_check()?;
+        let cond = self.parse_null_coalescing()?;
+        let until_stmts = self.parse_loop_block()?;
+        Ok(Box::new(UntilStatement::new(cond, until_stmts, l)))
+    }
+
+    fn parse_do_while(&mut self) -> Result<Box<DoWhileStatement>, (u64, u64)> {
+        self.advance();
+        let do_stmts = self.parse_loop_block()?;
+        self.eof_error_check()?;
+        let While = self.now() else {
             return Err(self.now_pos());
         };
+        let l = self.now_line();
         self.advance();
-        let _ = self.eof_error_check()?;
-        let mut while_stmts = vec![];
-        loop {
-            if let RightBrace = self.now() {
-                break;
-            }
-            while_stmts.push(self.parse_statement()?);
+        self.eof_error_check()?;
+        let cond = self.parse_null_coalescing()?;
+        self.eof_error_check()?;
+        if let SemiColon = self.now() {
+            self.advance();
+            Ok(Box::new(DoWhileStatement::new(do_stmts, cond, l)))
         }
+        else {
+            Err(self.now_pos())
+        }
+    }
+
+    fn parse_loop(&mut self) -> Result<Box<LoopStatement>, (u64, u64)> {
+        let l = self.now_line();
         self.advance();
-        Ok(Box::new(WhileStatement::new(cond, while_stmts, l)))
+        let loop_stmts = self.parse_loop_block()?;
+        Ok(Box::new(LoopStatement::new(loop_stmts, l)))
+    }
+
+    fn parse_exit(&mut self) -> Result<Box<ExitStatement>, (u64, u64)> {
+        let l = self.now_line();
+        self.advance();
+        self.eof_error_check()?;
+        let expr = self.parse_null_coalescing()?;
+        self.eof_error_check()?;
+        let SemiColon = self.now() else {
+            return Err(self.now_pos());
+        };
+        self.advance();
+        Ok(Box::new(ExitStatement::new(expr, l)))
+    }
+
+    fn parse_break_continue(&mut self) -> Result<Box<dyn Executable>, (u64, u64)> {
+        if self.loop_depth == 0 {
+            return Err(self.now_pos());
+        }
+        let l = self.now_line();
+        let stmt: Box<dyn Executable> = match self.now() {
+            Break => Box::new(BreakStatement::new(l)),
+            _ => Box::new(ContinueStatement::new(l))
+        };
+        self.advance();
+        self.eof_error_check()?;
+        if let SemiColon = self.now() {
+            self.advance();
+            Ok(stmt)
+        }
+        else {
+            Err(self.now_pos())
+        }
+    }
+
+    fn parse_null_coalescing(&mut self) -> Result<Expression, (u64, u64)> {
+        let mut expr = self.parse_logical_or()?;
+        while let QuestionQuestion = self.now() {
+            self.advance();
+            self.eof_error_check()?;
+            let right = self.parse_logical_or()?;
+            expr = Expression::new(expr, ExpressionType::NullCoalescing, right);
+        }
+        Ok(expr)
     }
 
     fn parse_logical_or(&mut self) -> Result<Expression, (u64, u64)> {
         let mut expr = self.parse_logical_and()?;
         while let LogicalOr = self.now() {
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_logical_and()?;
             expr = Expression::new(expr, ExpressionType::LogicalOr, right);
         }
@@ -237,7 +428,7 @@ impl Parser {
         let mut expr = self.parse_bitwise_or()?;
         while let LogicalAnd = self.now() {
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_bitwise_or()?;
             expr = Expression::new(expr, ExpressionType::LogicalAnd, right);
         }
@@ -248,7 +439,7 @@ impl Parser {
         let mut expr = self.parse_bitwise_xor()?;
         while let BitwiseOr = self.now() {
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_bitwise_xor()?;
             expr = Expression::new(expr, ExpressionType::BitwiseOr, right);
         }
@@ -259,7 +450,7 @@ impl Parser {
         let mut expr = self.parse_bitwise_and()?;
         while let BitwiseXor = self.now() {
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_bitwise_and()?;
             expr = Expression::new(expr, ExpressionType::BitwiseXor, right);
         }
@@ -270,7 +461,7 @@ impl Parser {
         let mut expr = self.parse_equality()?;
         while let BitwiseAnd = self.now() {
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_equality()?;
             expr = Expression::new(expr, ExpressionType::BitwiseAnd, right);
         }
@@ -278,7 +469,7 @@ impl Parser {
     }
 
     fn parse_equality(&mut self) -> Result<Expression, (u64, u64)> {
-        let mut expr = self.parse_comparison()?;
+        let mut expr = self.parse_membership()?;
         while let Equal | NotEqual = self.now() {
             let oper = match self.now() {
                 Equal => ExpressionType::Equal,
@@ -286,15 +477,27 @@ impl Parser {
                 _ => ExpressionType::Integer(0)
             };
             self.advance();
-            let _ = self.eof_error_check()?;
-            let right = self.parse_comparison()?;
+            self.eof_error_check()?;
+            let right = self.parse_membership()?;
             expr = Expression::new(expr, oper, right);
         }
         Ok(expr)
     }
 
+    fn parse_membership(&mut self) -> Result<Expression, (u64, u64)> {
+        let mut expr = self.parse_comparison()?;
+        while let In = self.now() {
+            self.advance();
+            self.eof_error_check()?;
+            let right = self.parse_comparison()?;
+            expr = Expression::new(expr, ExpressionType::In, right);
+        }
+        Ok(expr)
+    }
+
     fn parse_comparison(&mut self) -> Result<Expression, (u64, u64)> {
-        let mut expr = self.parse_hyper_1()?;
+        let expr = self.parse_range()?;
+        let mut rest = vec![];
         while let Greater | GreaterEqual | Lesser | LesserEqual = self.now() {
             let oper = match self.now() {
                 Greater => ExpressionType::Greater,
@@ -304,9 +507,30 @@ impl Parser {
                 _ => ExpressionType::Integer(0)
             };
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
+            let right = self.parse_range()?;
+            rest.push((oper, right));
+        }
+        if rest.len() <= 1 {
+            return Ok(match rest.pop() {
+                Some((oper, right)) => Expression::new(expr, oper, right),
+                None => expr
+            });
+        }
+        Ok(Expression::new_chain(expr, rest))
+    }
+
+    fn parse_range(&mut self) -> Result<Expression, (u64, u64)> {
+        let expr = self.parse_hyper_1()?;
+        if let DotDot | DotDotEqual = self.now() {
+            let oper = match self.now() {
+                DotDot => ExpressionType::HalfOpenRange,
+                _ => ExpressionType::InclusiveRange
+            };
+            self.advance();
+            self.eof_error_check()?;
             let right = self.parse_hyper_1()?;
-            expr = Expression::new(expr, oper, right);
+            return Ok(Expression::new(expr, oper, right));
         }
         Ok(expr)
     }
@@ -320,7 +544,7 @@ impl Parser {
                 _ => ExpressionType::Integer(0)
             };
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_hyper_2_remainder()?;
             expr = Expression::new(expr, oper, right);
         }
@@ -337,7 +561,7 @@ impl Parser {
                 _ => ExpressionType::Integer(0)
             };
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_unary()?;
             expr = Expression::new(expr, oper, right);
         }
@@ -345,7 +569,13 @@ impl Parser {
     }
 
     fn parse_unary(&mut self) -> Result<Expression, (u64, u64)> {
-        let _ = self.eof_error_check()?;
+        self.eof_error_check()?;
+        // 2^63 only fits an i64 once negated, so `-9223372036854775808` is read as one literal.
+        if let (Minus, Integer(n)) = (self.now(), self.peek()) && n == i64::MIN.unsigned_abs() {
+            self.advance();
+            self.advance();
+            return Ok(Expression::new_empty(ExpressionType::Integer(i64::MIN)));
+        }
         if let LogicalNot | BitwiseNot | Minus = self.now() {
             let oper = match self.now() {
                 LogicalNot => ExpressionType::LogicalNot,
@@ -354,28 +584,43 @@ impl Parser {
                 _ => ExpressionType::Integer(0)
             };
             self.advance();
-            let _ = self.eof_error_check()?;
+            self.eof_error_check()?;
             let right = self.parse_unary()?;
             return Ok(Expression::new_unary(oper, right));
         }
-        Ok(self.parse_primary()?)
+        self.parse_primary()
     }
 
     fn parse_primary(&mut self) -> Result<Expression, (u64, u64)> {
-        let _ = self.eof_error_check()?;
+        self.eof_error_check()?;
         match self.now() {
-            Integer(i) => {
+            Integer(n) => {
+                let i = i64::try_from(n).map_err(|_| self.now_pos())?;
                 self.advance();
                 Ok(Expression::new_empty(ExpressionType::Integer(i)))
             },
+            Nil => {
+                self.advance();
+                Ok(Expression::new_empty(ExpressionType::Nil))
+            },
+            True | False => {
+                let b = self.now() == True;
+                self.advance();
+                Ok(Expression::new_empty(ExpressionType::Boolean(b)))
+            },
             LeftParen => {
                 self.advance();
-                let _ = self.eof_error_check()?;
-                let expr = self.parse_logical_or()?;
-                let _ = self.eof_error_check()?;
+                self.eof_error_check()?;
+                let expr = self.parse_expression_list()?;
+                self.eof_error_check()?;
                 if let RightParen = self.now() {
                     self.advance();
-                    Ok(Expression::new_empty(ExpressionType::Parentheses(Box::new(expr))))
+                    if expr.is_tuple() {
+                        Ok(expr)
+                    }
+                    else {
+                        Ok(Expression::new_empty(ExpressionType::Parentheses(Box::new(expr))))
+                    }
                 }
                 else {
                     Err(self.now_pos())
@@ -383,7 +628,27 @@ impl Parser {
             },
             Identifier(i) => {
                 self.advance();
-                Ok(Expression::new_empty(ExpressionType::Variable(i)))
+                if self.is_end() || self.now() != LeftParen {
+                    return Ok(Expression::new_empty(ExpressionType::Variable(i)));
+                }
+                self.advance();
+                self.eof_error_check()?;
+                // Arguments are split here rather than by parse_expression_list, so `f((1, 2))`
+                // passes one tuple where `f(1, 2)` passes two integers.
+                let mut args = vec![];
+                if self.now() != RightParen {
+                    args.push(self.parse_null_coalescing()?);
+                    while !self.is_end() && self.now() == Comma {
+                        self.advance();
+                        args.push(self.parse_null_coalescing()?);
+                    }
+                }
+                self.eof_error_check()?;
+                let RightParen = self.now() else {
+                    return Err(self.now_pos());
+                };
+                self.advance();
+                Ok(Expression::new_empty(ExpressionType::Call(i, args)))
             },
             _ => Err(self.now_pos())
         }
@@ -0,0 +1,87 @@
+use std::{io::{self, BufRead, Write}, process::ExitCode};
+
+use minimal::{Error, Interpreter};
+
+/// Reads statements from standard input until it closes or a statement runs `exit`.
+/// A line that is a bare expression prints its value, and an unfinished block keeps reading lines.
+/// The session also ends, successfully, once standard output is closed.
+pub fn run(mut interpreter: Interpreter) -> ExitCode {
+    let mut out = io::stdout().lock();
+    match session(&mut interpreter, &mut out) {
+        Ok(code) => ExitCode::from(code.unwrap_or(0)),
+        // Whoever was reading, such as `head`, has stopped, so there is no one to answer.
+        Err(_) => ExitCode::SUCCESS
+    }
+}
+
+// Returns the status code when a statement runs `exit`, or an error once `out` cannot be written.
+fn session(interpreter: &mut Interpreter, out: &mut impl Write) -> io::Result<Option<u8>> {
+    let mut lines = io::stdin().lock().lines();
+    let mut source = String::new();
+    loop {
+        write!(out, "{}", if source.is_empty() { "> " } else { "... " })?;
+        out.flush()?;
+        let Some(Ok(line)) = lines.next() else {
+            writeln!(out)?;
+            return Ok(None);
+        };
+        if source.is_empty() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(command) = line.strip_prefix(':') {
+                if let Some(code) = run_command(interpreter, command, out)? {
+                    return Ok(Some(code));
+                }
+                continue;
+            }
+        }
+        source.push_str(&line);
+        source.push('\n');
+        match interpreter.eval_expr(&source) {
+            Ok(v) => writeln!(out, "{}", v)?,
+            Err(Error::Lexer { .. } | Error::Parser { .. }) => {
+                if !Interpreter::is_complete(&source) {
+                    continue;
+                }
+                if let Some(code) = run_source(interpreter, &source) {
+                    return Ok(Some(code));
+                }
+            }
+            Err(e) => eprintln!("{}", e)
+        }
+        source.clear();
+    }
+}
+
+// Returns the status code when the source runs `exit`.
+fn run_source(interpreter: &mut Interpreter, source: &str) -> Option<u8> {
+    interpreter.run_source(source).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        None
+    })
+}
+
+fn run_command(interpreter: &mut Interpreter, command: &str, out: &mut impl Write) -> io::Result<Option<u8>> {
+    let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+    match (name, argument.trim()) {
+        ("vars", "") => {
+            let mut globals: Vec<_> = interpreter.globals().collect();
+            globals.sort_by_key(|g| g.0);
+            for (name, value) in globals {
+                writeln!(out, "{} = {}", name, value)?;
+            }
+        }
+        ("reset", "") => interpreter.reset(),
+        ("load", path) if !path.is_empty() => {
+            let Ok(source) = std::fs::read_to_string(path) else {
+                eprintln!("Cannot read the file.");
+                return Ok(None);
+            };
+            return Ok(run_source(interpreter, &source));
+        }
+        _ => eprintln!("Unknown command. Use :vars, :reset or :load <file>.")
+    }
+    Ok(None)
+}
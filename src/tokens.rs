@@ -1,7 +1,9 @@
 #[derive(Debug, Clone, PartialEq)]
 pub enum Tokens {
-    Print, Println, Var, If, Else, While, Identifier(String),
-    Integer(i64), LogicalOr, BitwiseOr, LogicalAnd, BitwiseAnd, BitwiseXor,
+    Print, Println, Var, If, Else, While, Loop, Do, Until, Break, Continue, In, Pragma, Exit, Identifier(String),
+    Integer(u64), True, False, Nil, QuestionQuestion, LogicalOr, BitwiseOr, LogicalAnd, BitwiseAnd, BitwiseXor,
     Greater, GreaterEqual, Lesser, LesserEqual, Equal, Assign, NotEqual, BitwiseNot, LogicalNot,
-    Plus, Minus, Star, Slash, Percent, LeftParen, RightParen, LeftBrace, RightBrace, SemiColon
+    Plus, Minus, Star, Slash, Percent, DotDot, DotDotEqual, LeftParen, RightParen, LeftBrace, RightBrace, Comma, Colon, SemiColon,
+    // Never produced by the lexer; the parser reports it past the last token.
+    EndOfFile
 }
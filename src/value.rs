@@ -0,0 +1,132 @@
+use std::{fmt, mem, str::FromStr};
+
+use crate::checker::Type;
+
+/// A runtime value. Every expression evaluates to one and every variable holds one.
+/// Values of different types are never equal, so `1 == true` is false.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    Nil,
+    Tuple(Vec<Value>),
+    /// The integers from `start` up to `end`, which is included when `inclusive` is set.
+    /// Only the bounds are stored, however many integers the range spans.
+    Range { start: i64, end: i64, inclusive: bool }
+}
+
+impl Value {
+    /// Used by `if`, loop conditions and the logical operators:
+    /// `0`, `false`, `nil`, the empty tuple and an empty range are false, everything else is true.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Int(i) => *i != 0,
+            Self::Bool(b) => *b,
+            Self::Nil => false,
+            Self::Tuple(v) => !v.is_empty(),
+            Self::Range { start, end, inclusive } => start < end || *inclusive && start == end
+        }
+    }
+
+    /// The annotation that fits this value. `nil` fits any annotation.
+    pub fn type_of(&self) -> Option<Type> {
+        match self {
+            Self::Int(_) => Some(Type::Int),
+            Self::Bool(_) => Some(Type::Bool),
+            Self::Nil => None,
+            Self::Tuple(_) => Some(Type::Tuple),
+            Self::Range { .. } => Some(Type::Range)
+        }
+    }
+
+    /// Approximate bytes this value takes, including the components a tuple holds.
+    pub fn size(&self) -> usize {
+        mem::size_of::<Value>() + match self {
+            Self::Tuple(v) => v.iter().map(Value::size).sum(),
+            _ => 0
+        }
+    }
+
+    /// The integer an arithmetic, bitwise or ordering operator works on.
+    /// Booleans count as 0 and 1 unless `strict` is set.
+    pub fn as_int(&self, strict: bool) -> Result<i64, String> {
+        match self {
+            Self::Int(i) => Ok(*i),
+            Self::Bool(b) if !strict => Ok(*b as i64),
+            _ => Err(format!("'{}' Integer expected error", self))
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int(i) => write!(f, "{}", i),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Nil => write!(f, "nil"),
+            Self::Tuple(v) => {
+                write!(f, "(")?;
+                for (i, x) in v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", x)?;
+                }
+                // A one-component tuple prints as `(1,)` so it cannot be mistaken for `(1)`.
+                write!(f, "{})", if v.len() == 1 { "," } else { "" })
+            },
+            Self::Range { start, end, inclusive } => write!(f, "{}..{}{}", start, if *inclusive { "=" } else { "" }, end)
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(i: i64) -> Self {
+        Self::Int(i)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Nil, Into::into)
+    }
+}
+
+/// Reads `nil`, `true`, `false` or a decimal integer, as in script arguments.
+impl FromStr for Value {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nil" => Ok(Self::Nil),
+            "true" => Ok(Self::Bool(true)),
+            "false" => Ok(Self::Bool(false)),
+            _ => s.parse().map(Self::Int).map_err(|_| format!("'{}' Value expected error", s))
+        }
+    }
+}
+
+impl TryFrom<&Value> for i64 {
+    type Error = String;
+
+    fn try_from(v: &Value) -> Result<Self, Self::Error> {
+        v.as_int(true)
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = String;
+
+    fn try_from(v: &Value) -> Result<Self, Self::Error> {
+        match v {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(format!("'{}' Boolean expected error", v))
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::{Arc, Mutex};
+
+use minimal::{BufferOutput, Error, Interpreter, Value};
+
+#[test]
+fn set_global_respects_annotations() {
+    let mut interpreter = Interpreter::new();
+    interpreter.run_source("var n: int = 0; var b = true;").unwrap();
+    let expected = Error::Type("'n' Type mismatch error, expected int but found bool".to_string());
+    assert_eq!(interpreter.set_global("n", Value::Bool(true)), Err(expected));
+    assert_eq!(interpreter.get_global("n"), Some(&Value::Int(0)));
+    interpreter.set_global("n", Value::Int(5)).unwrap();
+    interpreter.set_global("n", Value::Nil).unwrap();
+    interpreter.set_global("b", Value::Int(1)).unwrap();
+    interpreter.set_global("fresh", Value::Bool(false)).unwrap();
+    assert_eq!(interpreter.get_global("fresh"), Some(&Value::Bool(false)));
+}
+
+#[test]
+fn step_limit_reports_where_it_stopped() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_step_limit(Some(100));
+    let result = interpreter.run_source("var i = 0;\nloop {\n    i = i + 1;\n}");
+    assert!(matches!(result, Err(Error::StepLimit(ref s)) if s.starts_with("Step limit exceeded at line ")), "{:?}", result);
+    let result = interpreter.run_source("loop {}");
+    assert_eq!(result, Err(Error::StepLimit("Step limit exceeded at line 1".to_string())));
+
+    interpreter.set_step_limit(Some(3));
+    assert_eq!(interpreter.eval_expr("1 + 2"), Ok(Value::Int(3)));
+    let result = interpreter.eval_expr("  1 + 2 + 3 + 4");
+    assert_eq!(result, Err(Error::StepLimit("Step limit exceeded at line 1 position 3".to_string())));
+}
+
+#[test]
+fn memory_limit_counts_every_variable() {
+    // Each variable costs its name's bytes plus one value, so "host", a, b and c fit exactly.
+    let limit = 7 + 4 * std::mem::size_of::<Value>();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_memory_limit(Some(limit));
+    interpreter.set_global("host", Value::Int(1)).unwrap();
+    let result = interpreter.run_source("var a = 1;\nvar b = 2;\nvar c = 3;\nvar d = 4;");
+    assert_eq!(result, Err(Error::Runtime("'d' Out of memory error at line 4".to_string())));
+    assert_eq!(interpreter.peak_memory(), limit);
+    interpreter.set_global("a", Value::Int(5)).unwrap();
+    assert_eq!(interpreter.set_global("e", Value::Int(5)), Err(Error::Runtime("'e' Out of memory error".to_string())));
+}
+
+#[test]
+fn cancel_before_a_run_stops_it() {
+    let mut interpreter = Interpreter::new();
+    interpreter.cancel_handle().cancel();
+    let result = interpreter.run_source("var x = 0;\nloop {}");
+    assert_eq!(result, Err(Error::Cancelled("Execution cancelled at line 1".to_string())));
+    interpreter.run_source("var y = 1;").unwrap();
+    assert_eq!(interpreter.eval_expr("y + 1"), Ok(Value::Int(2)));
+}
+
+#[test]
+fn cancel_from_another_thread() {
+    let mut interpreter = Interpreter::new();
+    let handle = interpreter.cancel_handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        handle.cancel();
+    });
+    let result = interpreter.run_source("var i = 0;\nloop {\n    i = i + 1;\n}");
+    canceller.join().unwrap();
+    assert!(matches!(result, Err(Error::Cancelled(_))), "{:?}", result);
+    assert!(matches!(interpreter.get_global("i"), Some(&Value::Int(i)) if i > 0));
+    interpreter.run_source("i = 0;").unwrap();
+}
+
+#[test]
+fn exit_is_not_an_error() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(interpreter.run_source("var x = 1;"), Ok(None));
+    assert_eq!(interpreter.run_source("while true { loop { exit 0; } }\nx = 2;"), Ok(Some(0)));
+    assert_eq!(interpreter.run_source("exit x + 2;"), Ok(Some(3)));
+    assert_eq!(interpreter.get_global("x"), Some(&Value::Int(1)));
+    let expected = Error::Runtime("'256' Exit code out of range error at line 1".to_string());
+    assert_eq!(interpreter.run_source("exit 256;"), Err(expected));
+}
+
+#[test]
+fn tuples_are_values() {
+    let mut interpreter = Interpreter::new();
+    interpreter.run_source("var t = (1, 2);\nvar (a, b) = t;\nvar c = 2 in t;\nvar n = (t, true);").unwrap();
+    assert_eq!(interpreter.get_global("t"), Some(&Value::Tuple(vec![Value::Int(1), Value::Int(2)])));
+    assert_eq!(interpreter.get_global("b"), Some(&Value::Int(2)));
+    assert_eq!(interpreter.get_global("c"), Some(&Value::Bool(true)));
+    assert_eq!(interpreter.eval_expr("n"), Ok(Value::Tuple(vec![Value::Tuple(vec![Value::Int(1), Value::Int(2)]), Value::Bool(true)])));
+    assert_eq!(interpreter.eval_expr("t == (1, 2)"), Ok(Value::Bool(true)));
+    let result = interpreter.run_source("var x: int = (1, 2);");
+    assert_eq!(result, Err(Error::Type("'x' Type mismatch error, expected int but found tuple at line 1 position 14".to_string())));
+}
+
+#[test]
+fn memory_limit_counts_tuple_components() {
+    let size = std::mem::size_of::<Value>();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_memory_limit(Some(2 + 4 * size));
+    interpreter.run_source("var t = 1;\nt = (1, 2);").unwrap();
+    assert_eq!(interpreter.peak_memory(), 1 + 3 * size);
+    let result = interpreter.run_source("t = (1, 2, 3, 4);");
+    assert_eq!(result, Err(Error::Runtime("'t' Out of memory error at line 1".to_string())));
+    interpreter.run_source("t = 0;\nvar u = (1, 2);").unwrap();
+}
+
+#[test]
+fn built_ins_return_tuples() {
+    let output = BufferOutput::new();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(output.clone());
+    interpreter.run_source("println sort(3, 1, 2);\nprintln reverse(1, 2);\nprintln sort(5);\nprintln divmod(7, 2);").unwrap();
+    assert_eq!(output.take(), "(1, 2, 3)\n(2, 1)\n(5,)\n(3, 1)\n");
+    assert_eq!(interpreter.eval_expr("sum(sort(3, 1, 2))"), Ok(Value::Int(6)));
+    interpreter.run_source("var (lo, mid, hi) = sort(3, 1, 2);").unwrap();
+    assert_eq!(interpreter.get_global("hi"), Some(&Value::Int(3)));
+}
+
+#[test]
+fn ranges_are_values() {
+    let mut interpreter = Interpreter::new();
+    interpreter.run_source("var r = 0..10;\nvar s: range = 1..=3;").unwrap();
+    assert_eq!(interpreter.get_global("r"), Some(&Value::Range { start: 0, end: 10, inclusive: false }));
+    assert_eq!(interpreter.eval_expr("9 in r"), Ok(Value::Bool(true)));
+    assert_eq!(interpreter.eval_expr("10 in r"), Ok(Value::Bool(false)));
+    assert_eq!(interpreter.eval_expr("3 in s"), Ok(Value::Bool(true)));
+    assert_eq!(interpreter.eval_expr("true in 0..2"), Ok(Value::Bool(true)));
+    interpreter.set_strict(true);
+    assert_eq!(interpreter.eval_expr("true in 0..2"), Err(Error::Runtime("'true' Integer expected error at line 1 position 1".to_string())));
+}
+
+#[test]
+fn annotations_are_enforced() {
+    let mut interpreter = Interpreter::new();
+    let result = interpreter.run_source("var (q: bool, r) = divmod(7, 2);");
+    assert_eq!(result, Err(Error::Type("'q' Type mismatch error, expected bool but found int at line 1 position 20".to_string())));
+    let result = interpreter.run_source("var b = true;\nvar m: int = b;");
+    assert_eq!(result, Err(Error::Type("'m' Type mismatch error, expected int but found bool at line 2 position 14".to_string())));
+    let result = interpreter.run_source("var n: int = nil ?? true;");
+    assert_eq!(result, Err(Error::Type("'n' Type mismatch error, expected int but found bool at line 1 position 14".to_string())));
+
+    // Values whose type is only known at runtime are checked when they are stored.
+    interpreter.set_args(vec![Value::Bool(true)]);
+    let result = interpreter.run_source("var x: int = 0;\nx = args(0);");
+    assert_eq!(result, Err(Error::Runtime("'x' Type mismatch error, expected int but found bool at line 2 position 5".to_string())));
+    assert_eq!(interpreter.get_global("x"), Some(&Value::Int(0)));
+    let result = interpreter.run_source("var (y: int, z) = (args(0), 1);");
+    assert_eq!(result, Err(Error::Runtime("'y' Type mismatch error, expected int but found bool at line 1 position 19".to_string())));
+    assert_eq!(interpreter.get_global("z"), None);
+
+    // A variable the program assigns to can hold either type when read.
+    interpreter.run_source("var c = true;\nc = 1;\nvar k: int = c;").unwrap();
+    interpreter.run_source("var d = true;").unwrap();
+    interpreter.set_global("d", Value::Int(1)).unwrap();
+    interpreter.run_source("var e: int = d;").unwrap();
+}
+
+#[test]
+fn smallest_integer_can_be_written() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(interpreter.eval_expr("-9223372036854775808"), Ok(Value::Int(i64::MIN)));
+    assert_eq!(interpreter.eval_expr("-0x8000_0000_0000_0000"), Ok(Value::Int(i64::MIN)));
+    assert_eq!(interpreter.eval_expr("9223372036854775808"), Err(Error::Parser { line: 1, position: 1 }));
+    assert_eq!(interpreter.eval_expr("-9223372036854775809"), Err(Error::Lexer { line: 1, position: 2 }));
+}
+
+#[test]
+fn host_functions_are_called_from_scripts() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let mut interpreter = Interpreter::new();
+    let log = events.clone();
+    interpreter.register_function("log_event", 1, move |args| {
+        log.lock().unwrap().push(args[0].clone());
+        Ok(Value::Nil)
+    });
+    interpreter.register_function("double", 1, |args| Ok(Value::Int(i64::try_from(&args[0])? * 2)));
+    interpreter.register_function("fail", 0, |_| Err("disk full".to_string()));
+
+    interpreter.run_source("var id = double(21);\nlog_event(id);\nlog_event((id, true));").unwrap();
+    assert_eq!(*events.lock().unwrap(), vec![Value::Int(42), Value::Tuple(vec![Value::Int(42), Value::Bool(true)])]);
+
+    let result = interpreter.run_source("log_event(1, 2);");
+    assert_eq!(result, Err(Error::Runtime("'log_event' Argument count error at line 1".to_string())));
+    let result = interpreter.run_source("\nfail();");
+    assert_eq!(result, Err(Error::Runtime("'fail' Host function error: disk full at line 2".to_string())));
+    let result = interpreter.eval_expr("double(true)");
+    assert_eq!(result, Err(Error::Runtime("'double' Host function error: 'true' Integer expected error at line 1 position 1".to_string())));
+}
@@ -0,0 +1,25 @@
+use std::io;
+
+use minimal::{BufferOutput, CallbackOutput, Error, Interpreter, Value};
+
+#[test]
+fn buffer_output_captures_prints() {
+    let output = BufferOutput::new();
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(output.clone());
+    interpreter.run_source("var i = 1; while i <= 3 { print i; i = i + 1; } println; println i;").unwrap();
+    assert_eq!(output.take(), "1 2 3 \n4\n");
+    assert_eq!(output.contents(), "");
+}
+
+#[test]
+fn failing_callback_output_is_a_runtime_error() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_output(CallbackOutput(|_: &str| Err(io::Error::from(io::ErrorKind::BrokenPipe))));
+    let Err(Error::Runtime(message)) = interpreter.run_source("var x = 1;\nprintln x;") else {
+        panic!("expected a runtime error");
+    };
+    assert!(message.starts_with("Output error ("), "{}", message);
+    assert!(message.ends_with("at line 2"), "{}", message);
+    assert_eq!(interpreter.get_global("x"), Some(&Value::Int(1)));
+}